@@ -1,5 +1,5 @@
 use crate::config::PactupConfig;
-use crate::fs::{remove_symlink_dir, shallow_read_symlink, symlink_dir};
+use crate::fs::{replace_symlink_dir, shallow_read_symlink};
 use crate::system_version;
 use crate::version::Version;
 use std::convert::TryInto;
@@ -16,15 +16,14 @@ pub fn create_alias(
   let version_dir = version.installation_path(config);
   let alias_dir = aliases_dir.join(common_name);
 
-  remove_symlink_dir(&alias_dir).ok();
-  symlink_dir(version_dir, &alias_dir)?;
-
-  Ok(())
+  replace_symlink_dir(version_dir, alias_dir)
 }
 
 pub fn list_aliases(config: &PactupConfig) -> std::io::Result<Vec<StoredAlias>> {
   let vec: Vec<_> = std::fs::read_dir(config.aliases_dir())?
     .filter_map(Result::ok)
+    // Leaves out the links being created, see `replace_symlink_dir`.
+    .filter(|x| !x.file_name().to_string_lossy().starts_with('.'))
     .filter_map(|x| TryInto::<StoredAlias>::try_into(x.path().as_path()).ok())
     .collect();
   Ok(vec)
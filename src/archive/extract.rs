@@ -1,5 +1,6 @@
 use std::error::Error as StdError;
-use std::path::Path;
+use std::io::{Read, Write};
+use std::path::{Component, Path, PathBuf};
 
 #[derive(Debug)]
 pub enum Error {
@@ -7,6 +8,10 @@ pub enum Error {
   ZipError(zip::result::ZipError),
   HttpError(crate::http::Error),
   UnknownArchiveType { content_type: String },
+  TooLarge { limit: u64 },
+  TooManyEntries { limit: usize },
+  LinkEscape { link: PathBuf, target: PathBuf },
+  FileOverDirectory { path: PathBuf },
 }
 
 impl std::fmt::Display for Error {
@@ -18,6 +23,25 @@ impl std::fmt::Display for Error {
       Self::UnknownArchiveType { content_type } => {
         write!(f, "Unknown archive type: {content_type}")
       }
+      Self::TooLarge { limit } => write!(
+        f,
+        "The archive expands to more than {}",
+        indicatif::HumanBytes(*limit)
+      ),
+      Self::TooManyEntries { limit } => {
+        write!(f, "The archive holds more than {limit} entries")
+      }
+      Self::LinkEscape { link, target } => write!(
+        f,
+        "The link {} points to {}, outside of the archive",
+        link.display(),
+        target.display()
+      ),
+      Self::FileOverDirectory { path } => write!(
+        f,
+        "The archive holds both a directory and a file at {}",
+        path.display()
+      ),
     }
   }
 }
@@ -48,5 +72,137 @@ impl From<walkdir::Error> for Error {
   }
 }
 pub trait Extract {
-  fn extract_into(self: Box<Self>, path: &Path) -> Result<(), Error>;
+  fn extract_into(self: Box<Self>, path: &Path, limits: Limits) -> Result<(), Error>;
+}
+
+/// What an archive may expand to, so that an archive bomb can't fill the disk.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Limits {
+  /// The total size of the extracted files, in bytes.
+  pub max_size: u64,
+  pub max_entries: usize,
+}
+
+impl Default for Limits {
+  /// Far more than any Pact release needs.
+  fn default() -> Self {
+    Self {
+      max_size: 4 * 1024 * 1024 * 1024,
+      max_entries: 100_000,
+    }
+  }
+}
+
+/// What an archive expanded to so far, checked against its [`Limits`].
+#[derive(Debug)]
+pub struct Budget {
+  limits: Limits,
+  size: u64,
+  entries: usize,
+}
+
+impl Budget {
+  pub fn new(limits: Limits) -> Self {
+    Self {
+      limits,
+      size: 0,
+      entries: 0,
+    }
+  }
+
+  pub fn add_entry(&mut self) -> Result<(), Error> {
+    self.entries += 1;
+    if self.entries > self.limits.max_entries {
+      return Err(Error::TooManyEntries {
+        limit: self.limits.max_entries,
+      });
+    }
+    Ok(())
+  }
+
+  /// Accounts for an entry whose size is known upfront, before extracting it.
+  pub fn add_size(&mut self, size: u64) -> Result<(), Error> {
+    self.size = self.size.saturating_add(size);
+    if self.size > self.limits.max_size {
+      return Err(Error::TooLarge {
+        limit: self.limits.max_size,
+      });
+    }
+    Ok(())
+  }
+
+  /// Copies an entry whose declared size can't be trusted, stopping as soon as the limit is exceeded.
+  pub fn copy(&mut self, reader: &mut impl Read, writer: &mut impl Write) -> Result<(), Error> {
+    let left = self.limits.max_size.saturating_sub(self.size);
+    let copied = std::io::copy(&mut reader.take(left.saturating_add(1)), writer)?;
+    self.add_size(copied)
+  }
+}
+
+/// The mode of an extracted file: readable by all, writable by its owner, and executable by all if it
+/// was by anyone. The setuid, setgid and sticky bits of the archive are dropped.
+pub fn safe_mode(mode: u32, is_dir: bool) -> u32 {
+  if is_dir || mode & 0o111 != 0 {
+    0o755
+  } else {
+    0o644
+  }
+}
+
+/// Checks that the link at `link` in `root`, pointing to `target`, doesn't lead outside of `root`.
+/// The target of a symbolic link is relative to the directory of the link, the one of a hard link to `root`.
+pub fn check_link(root: &Path, link: &Path, target: &Path, hard: bool) -> Result<(), Error> {
+  let link: PathBuf = link
+    .components()
+    .filter(|component| matches!(component, Component::Normal(_)))
+    .collect();
+  let base = if hard {
+    root.to_path_buf()
+  } else {
+    root.join(link.parent().unwrap_or(Path::new("")))
+  };
+  let root = std::fs::canonicalize(root)?;
+  let escapes = target.has_root()
+    || resolve(&base.join(target)).is_none_or(|resolved| !resolved.starts_with(&root));
+  if escapes {
+    return Err(Error::LinkEscape {
+      link,
+      target: target.to_path_buf(),
+    });
+  }
+  Ok(())
+}
+
+/// Checks the symbolic links extracted into `root` once all of them are there, as a link may lead
+/// outside through another link extracted later.
+pub fn check_links(root: &Path) -> Result<(), Error> {
+  for entry in walkdir::WalkDir::new(root) {
+    let entry = entry?;
+    if entry.path_is_symlink() {
+      let target = std::fs::read_link(entry.path())?;
+      let link = entry.path().strip_prefix(root).unwrap_or(entry.path());
+      check_link(root, link, &target, false)?;
+    }
+  }
+  Ok(())
+}
+
+/// Resolves `path` like [`std::fs::canonicalize`], although it may not exist: its longest existing
+/// ancestor is canonicalized, and the rest of it is appended.
+fn resolve(path: &Path) -> Option<PathBuf> {
+  let components: Vec<Component<'_>> = path.components().collect();
+  (1..=components.len()).rev().find_map(|split| {
+    let prefix: PathBuf = components[..split].iter().collect();
+    let mut resolved = std::fs::canonicalize(prefix).ok()?;
+    for component in &components[split..] {
+      match component {
+        Component::ParentDir => {
+          resolved.pop();
+        }
+        Component::Normal(name) => resolved.push(name),
+        _ => {}
+      }
+    }
+    Some(resolved)
+  })
 }
@@ -2,43 +2,108 @@ pub mod extract;
 pub mod tar;
 pub mod zip;
 
-use std::io::Read;
+use std::io::{Chain, Cursor, Read};
 use std::path::Path;
 
-pub use self::extract::{Error, Extract};
+pub use self::extract::{Error, Extract, Limits};
 use self::tar::Tar;
 use self::zip::Zip;
 
+/// The longest magic number [`Archive::sniff`] looks for.
+const MAGIC_LEN: usize = 6;
+
+/// A reader yielding the bytes [`Archive::sniff`] read, then the rest of the stream.
+pub type Sniffed<R> = Chain<Cursor<Vec<u8>>, R>;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Archive {
   Zip,
   TarXz,
   TarGz,
+  TarZst,
+  TarBz2,
 }
 impl Archive {
-  pub fn extract_archive_into(path: &Path, response: impl Read, url: &str) -> Result<(), Error> {
-    let extractor: Box<dyn Extract> = match Archive::from_url(url) {
-      Some(Self::Zip) => Box::new(Zip::new(response)),
-      Some(Self::TarXz) => Box::new(Tar::Xz(response)),
-      Some(Self::TarGz) => Box::new(Tar::Gz(response)),
-      None => {
-        return Err(Error::UnknownArchiveType {
+  pub fn as_str(self) -> &'static str {
+    match self {
+      Self::Zip => "zip",
+      Self::TarXz => "tar.xz",
+      Self::TarGz => "tar.gz",
+      Self::TarZst => "tar.zst",
+      Self::TarBz2 => "tar.bz2",
+    }
+  }
+
+  /// Extracts an archive read from `reader` into `path`. Its type is told from its first bytes,
+  /// or else from its `url`, as the URL of a redirect or an artifact server may have no extension.
+  pub fn extract_archive_into(path: &Path, reader: impl Read, url: &str) -> Result<(), Error> {
+    let (sniffed, reader) = Self::sniff(reader)?;
+    let archive =
+      sniffed
+        .or_else(|| Self::from_url(url))
+        .ok_or_else(|| Error::UnknownArchiveType {
           content_type: url.to_string(),
-        })
-      }
+        })?;
+    archive.extract_into(path, reader)
+  }
+
+  /// Tells the type of an archive from its magic number, and returns a reader yielding all of it,
+  /// the bytes read to find out included.
+  pub fn sniff<R: Read>(mut reader: R) -> std::io::Result<(Option<Self>, Sniffed<R>)> {
+    let mut magic = Vec::with_capacity(MAGIC_LEN);
+    (&mut reader)
+      .take(MAGIC_LEN as u64)
+      .read_to_end(&mut magic)?;
+    let archive = match &magic[..] {
+      [0x1f, 0x8b, ..] => Some(Self::TarGz),
+      [0xfd, b'7', b'z', b'X', b'Z', 0x00] => Some(Self::TarXz),
+      [0x28, 0xb5, 0x2f, 0xfd, ..] => Some(Self::TarZst),
+      [b'B', b'Z', b'h', ..] => Some(Self::TarBz2),
+      [b'P', b'K', 0x03, 0x04, ..] | [b'P', b'K', 0x05, 0x06, ..] => Some(Self::Zip),
+      _ => None,
     };
-    extractor.extract_into(path)?;
+    Ok((archive, Cursor::new(magic).chain(reader)))
+  }
+
+  /// Extracts an archive of this type, read from `reader`, into `path`.
+  pub fn extract_into(self, path: &Path, reader: impl Read) -> Result<(), Error> {
+    self.extract_with_limits(path, reader, Limits::default())
+  }
+
+  /// Extracts an archive of this type into `path`, failing if it expands past `limits`.
+  pub fn extract_with_limits(
+    self,
+    path: &Path,
+    reader: impl Read,
+    limits: Limits,
+  ) -> Result<(), Error> {
+    let extractor: Box<dyn Extract> = match self {
+      Self::Zip => Box::new(Zip::new(reader)),
+      Self::TarXz => Box::new(Tar::Xz(reader)),
+      Self::TarGz => Box::new(Tar::Gz(reader)),
+      Self::TarZst => Box::new(Tar::Zst(reader)),
+      Self::TarBz2 => Box::new(Tar::Bz2(reader)),
+    };
+    extractor.extract_into(path, limits)?;
     Ok(())
   }
 
   pub fn from_url(url: &str) -> Option<Self> {
+    let url = url.split(['?', '#']).next().unwrap_or(url);
+    let has_extension = |extension: &str| {
+      std::path::Path::new(url)
+        .extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case(extension))
+    };
     if url.ends_with(".tar.xz") {
       Some(Self::TarXz)
-    } else if url.ends_with(".tar.gz") {
+    } else if url.ends_with(".tar.gz") || has_extension("tgz") {
       Some(Self::TarGz)
-    } else if std::path::Path::new(url)
-      .extension()
-      .is_some_and(|ext| ext.eq_ignore_ascii_case("zip"))
-    {
+    } else if url.ends_with(".tar.zst") || has_extension("tzst") {
+      Some(Self::TarZst)
+    } else if url.ends_with(".tar.bz2") || has_extension("tbz2") {
+      Some(Self::TarBz2)
+    } else if has_extension("zip") {
       Some(Self::Zip)
     } else {
       None
@@ -46,6 +111,111 @@ impl Archive {
   }
 
   pub fn supported() -> &'static [Self] {
-    &[Self::TarXz, Self::TarGz, Self::Zip]
+    &[
+      Self::TarXz,
+      Self::TarGz,
+      Self::TarZst,
+      Self::TarBz2,
+      Self::Zip,
+    ]
+  }
+}
+
+impl std::str::FromStr for Archive {
+  type Err = String;
+
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    Self::supported()
+      .iter()
+      .find(|archive| archive.as_str() == s)
+      .copied()
+      .ok_or_else(|| {
+        let supported: Vec<&str> = Self::supported().iter().map(|x| x.as_str()).collect();
+        format!("expected one of {}", supported.join(", "))
+      })
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use pretty_assertions::assert_eq;
+  use std::io::Write;
+
+  fn tarball() -> Vec<u8> {
+    let mut builder = ::tar::Builder::new(vec![]);
+    let mut header = ::tar::Header::new_gnu();
+    header.set_size(4);
+    header.set_mode(0o755);
+    builder
+      .append_data(&mut header, "pact-5.1.0/pact", &b"pact"[..])
+      .unwrap();
+    builder.into_inner().unwrap()
+  }
+
+  fn compress(archive: Archive) -> Vec<u8> {
+    let tarball = tarball();
+    match archive {
+      Archive::TarGz => {
+        let mut encoder = flate2::write::GzEncoder::new(vec![], flate2::Compression::default());
+        encoder.write_all(&tarball).unwrap();
+        encoder.finish().unwrap()
+      }
+      Archive::TarXz => {
+        let mut encoder = xz2::write::XzEncoder::new(vec![], 6);
+        encoder.write_all(&tarball).unwrap();
+        encoder.finish().unwrap()
+      }
+      Archive::TarZst => zstd::stream::encode_all(&tarball[..], 0).unwrap(),
+      Archive::TarBz2 => {
+        let mut encoder = bzip2::write::BzEncoder::new(vec![], bzip2::Compression::default());
+        encoder.write_all(&tarball).unwrap();
+        encoder.finish().unwrap()
+      }
+      Archive::Zip => {
+        let mut writer = ::zip::ZipWriter::new(Cursor::new(vec![]));
+        let options = ::zip::write::SimpleFileOptions::default().unix_permissions(0o755);
+        writer.start_file("pact-5.1.0/pact", options).unwrap();
+        writer.write_all(b"pact").unwrap();
+        writer.finish().unwrap().into_inner()
+      }
+    }
+  }
+
+  #[test]
+  fn test_extracts_archives_told_apart_by_their_contents() {
+    for &archive in Archive::supported() {
+      let data = compress(archive);
+      let (sniffed, mut reader) = Archive::sniff(&data[..]).unwrap();
+      assert_eq!(sniffed, Some(archive));
+      let mut read = vec![];
+      reader.read_to_end(&mut read).unwrap();
+      assert_eq!(read, data);
+
+      let dir = tempfile::tempdir().unwrap();
+      Archive::extract_archive_into(dir.path(), &data[..], "https://example.com/download?id=1")
+        .unwrap();
+      let extracted = dir.path().join("pact-5.1.0").join("pact");
+      assert_eq!(std::fs::read(extracted).unwrap(), b"pact", "{archive:?}");
+    }
+  }
+
+  #[test]
+  fn test_unknown_archive_type() {
+    let dir = tempfile::tempdir().unwrap();
+    let result = Archive::extract_archive_into(dir.path(), &b"<html>"[..], "https://example.com/x");
+    assert!(matches!(result, Err(Error::UnknownArchiveType { .. })));
+    assert_eq!(Archive::sniff(&b"PK"[..]).unwrap().0, None);
+  }
+
+  #[test]
+  fn test_from_url() {
+    assert_eq!(
+      Archive::from_url("https://example.com/pact-5.1.0.tar.zst?token=1"),
+      Some(Archive::TarZst)
+    );
+    assert_eq!(Archive::from_url("pact-5.1.0.tbz2"), Some(Archive::TarBz2));
+    assert_eq!(Archive::from_url("pact-5.1.0.ZIP"), Some(Archive::Zip));
+    assert_eq!(Archive::from_url("https://example.com/download"), None);
   }
 }
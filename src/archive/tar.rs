@@ -1,9 +1,11 @@
 use log::debug;
 
-use super::extract::{Error, Extract};
+use super::extract::{check_link, check_links, Budget, Error, Extract, Limits};
 
 use std::{io::Read, path::Path};
 
+#[cfg(unix)]
+use super::extract::safe_mode;
 #[cfg(unix)]
 use std::os::unix::fs::PermissionsExt;
 
@@ -12,70 +14,190 @@ pub enum Tar<R: Read> {
   Xz(R),
   /// Tar archive with Gzip compression
   Gz(R),
+  /// Tar archive with Zstandard compression
+  Zst(R),
+  /// Tar archive with Bzip2 compression
+  Bz2(R),
 }
 impl<R: Read> Tar<R> {
-  fn extract_into_impl<P: AsRef<Path>>(self, path: P) -> Result<(), Error> {
+  fn extract_into_impl<P: AsRef<Path>>(self, path: P, limits: Limits) -> Result<(), Error> {
+    let path = path.as_ref();
     debug!("Decompressing tar archive");
     let stream: Box<dyn Read> = match self {
       Self::Xz(response) => Box::new(xz2::read::XzDecoder::new(response)),
       Self::Gz(response) => Box::new(flate2::read::GzDecoder::new(response)),
+      Self::Zst(response) => Box::new(zstd::stream::read::Decoder::new(response)?),
+      Self::Bz2(response) => Box::new(bzip2::read::MultiBzDecoder::new(response)),
     };
     let mut tar_archive = tar::Archive::new(stream);
     tar_archive.set_preserve_permissions(false);
     tar_archive.set_preserve_ownerships(false);
     tar_archive.set_overwrite(true);
-    debug!("Extracting tar archive into {:?}", path.as_ref());
-    // First, extract everything, even if the permissions are restrictive
-    tar_archive.unpack(&path)?;
+    debug!("Extracting tar archive into {:?}", path);
+    let mut budget = Budget::new(limits);
+    for entry in tar_archive.entries()? {
+      let mut entry = entry?;
+      budget.add_entry()?;
+      // The data of an entry is exactly as long as its header says.
+      budget.add_size(entry.size())?;
+      let entry_type = entry.header().entry_type();
+      if entry_type.is_symlink() || entry_type.is_hard_link() {
+        if let Some(target) = entry.link_name()? {
+          check_link(path, &entry.path()?, &target, entry_type.is_hard_link())?;
+        }
+      }
+      // Entries with `..` in their path are skipped, and the parent directories of the others must
+      // resolve inside `path`, so that no file is written through a link.
+      entry.unpack_in(path)?;
+    }
+    check_links(path)?;
 
     debug!("Fixing permissions for extracted files and directories");
     // Now recursively set permissions for all directories and files
-    fix_permissions_recursively(path.as_ref())?;
+    fix_permissions_recursively(path)?;
     Ok(())
   }
 }
 impl<R: Read> Extract for Tar<R> {
-  fn extract_into(self: Box<Self>, path: &Path) -> Result<(), Error> {
-    self.extract_into_impl(path)
+  fn extract_into(self: Box<Self>, path: &Path, limits: Limits) -> Result<(), Error> {
+    self.extract_into_impl(path, limits)
   }
 }
 
 // Helper function to recursively fix permissions cross-platform
 fn fix_permissions_recursively<P: AsRef<Path>>(path: P) -> Result<(), Error> {
-  // Iterate over all files and directories recursively
+  // Iterate over all files and directories recursively, without following links
   for entry in walkdir::WalkDir::new(path) {
     let entry = entry?;
-    let path = entry.path();
 
     // Set permissions for Unix-like systems (Linux, macOS)
     #[cfg(unix)]
     {
-      // Set permissions for directories
-      if entry.file_type().is_dir() {
-        let mut dir_permissions = std::fs::metadata(path)?.permissions();
-        dir_permissions.set_mode(0o755); // Default directory permissions (rwxr-xr-x)
-        std::fs::set_permissions(path, dir_permissions)?;
+      // Links are left alone, changing their permissions would change the ones of their target
+      if entry.file_type().is_dir() || entry.file_type().is_file() {
+        let mut permissions = entry.metadata()?.permissions();
+        permissions.set_mode(safe_mode(permissions.mode(), entry.file_type().is_dir()));
+        std::fs::set_permissions(entry.path(), permissions)?;
       }
-      // Set permissions for regular files, preserving executable bits for all
-      else if entry.file_type().is_file() {
-        let metadata = std::fs::metadata(path)?;
-        let mut file_permissions = metadata.permissions();
-
-        // Default file permissions (rw-r--r--)
-        let mut mode = 0o644;
-
-        // Check if the file is executable (user, group, or others)
-        if file_permissions.mode() & 0o111 != 0 {
-          // If any of the executable bits are set, keep them for user, group, and others
-          mode |= 0o111; // Retain all executable bits (for user, group, and others)
-        }
+    }
+  }
+
+  Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use pretty_assertions::assert_eq;
+  use std::io::Write;
 
-        // Apply the computed mode
-        file_permissions.set_mode(mode);
-        std::fs::set_permissions(path, file_permissions)?;
+  /// A gzipped tarball of the given entries: a path, an entry type, a mode, and the data or link target.
+  fn tarball(entries: &[(&str, tar::EntryType, u32, &str)]) -> Vec<u8> {
+    let mut builder = tar::Builder::new(vec![]);
+    for &(path, entry_type, mode, data) in entries {
+      let mut header = tar::Header::new_gnu();
+      header.set_entry_type(entry_type);
+      header.set_mode(mode);
+      if entry_type.is_file() {
+        header.set_size(data.len() as u64);
+        builder
+          .append_data(&mut header, path, data.as_bytes())
+          .unwrap();
+      } else {
+        header.set_size(0);
+        builder.append_link(&mut header, path, data).unwrap();
       }
     }
+    let mut encoder = flate2::write::GzEncoder::new(vec![], flate2::Compression::default());
+    encoder.write_all(&builder.into_inner().unwrap()).unwrap();
+    encoder.finish().unwrap()
   }
 
-  Ok(())
+  fn extract(data: &[u8], limits: Limits) -> (tempfile::TempDir, Result<(), Error>) {
+    let dir = tempfile::tempdir().unwrap();
+    let result = Box::new(Tar::Gz(data)).extract_into(dir.path(), limits);
+    (dir, result)
+  }
+
+  const FILE: tar::EntryType = tar::EntryType::Regular;
+  const SYMLINK: tar::EntryType = tar::EntryType::Symlink;
+  const HARD_LINK: tar::EntryType = tar::EntryType::Link;
+
+  #[test]
+  #[cfg(unix)]
+  fn test_keeps_links_inside_the_archive_and_strips_setuid() {
+    let data = tarball(&[
+      ("pact/libexec/pact", FILE, 0o4755, "pact"),
+      ("pact/bin/pact", SYMLINK, 0o777, "../libexec/pact"),
+      ("pact/bin/pact-copy", HARD_LINK, 0o755, "pact/libexec/pact"),
+    ]);
+    let (dir, result) = extract(&data, Limits::default());
+    result.unwrap();
+
+    let binary = dir.path().join("pact/bin/pact");
+    assert_eq!(
+      std::fs::read_link(&binary).unwrap(),
+      Path::new("../libexec/pact")
+    );
+    assert_eq!(std::fs::read_to_string(binary).unwrap(), "pact");
+    let mode = std::fs::metadata(dir.path().join("pact/libexec/pact"))
+      .unwrap()
+      .permissions()
+      .mode();
+    assert_eq!(mode & 0o7777, 0o755);
+  }
+
+  #[test]
+  #[cfg(unix)]
+  fn test_refuses_links_escaping_the_archive() {
+    for entries in [
+      &[("pact/etc", SYMLINK, 0o777, "../../etc")][..],
+      &[("pact/passwd", SYMLINK, 0o777, "/etc/passwd")],
+      &[("pact/passwd", HARD_LINK, 0o644, "../passwd")],
+      // Each link stays inside on its own, but `a` turns `x` into `pact/../../..`.
+      &[
+        ("pact/x", SYMLINK, 0o777, "a/../.."),
+        ("pact/a", SYMLINK, 0o777, ".."),
+      ],
+    ] {
+      let (_dir, result) = extract(&tarball(entries), Limits::default());
+      assert!(
+        matches!(result, Err(Error::LinkEscape { .. })),
+        "{entries:?}: {result:?}"
+      );
+    }
+  }
+
+  #[test]
+  fn test_limits() {
+    let data = tarball(&[
+      ("pact/a", FILE, 0o644, "12345"),
+      ("pact/b", FILE, 0o644, "12345"),
+      ("pact/c", FILE, 0o644, "12345"),
+    ]);
+    let (_dir, result) = extract(
+      &data,
+      Limits {
+        max_size: 12,
+        max_entries: 10,
+      },
+    );
+    assert!(matches!(result, Err(Error::TooLarge { limit: 12 })));
+    let (_dir, result) = extract(
+      &data,
+      Limits {
+        max_size: 100,
+        max_entries: 2,
+      },
+    );
+    assert!(matches!(result, Err(Error::TooManyEntries { limit: 2 })));
+    let (_dir, result) = extract(
+      &data,
+      Limits {
+        max_size: 15,
+        max_entries: 3,
+      },
+    );
+    assert!(result.is_ok());
+  }
 }
@@ -1,4 +1,4 @@
-use super::extract::{Error, Extract};
+use super::extract::{check_link, check_links, safe_mode, Budget, Error, Extract, Limits};
 use log::debug;
 use std::fs;
 use std::io::{self, Read};
@@ -18,7 +18,7 @@ impl<R: Read> Zip<R> {
 }
 
 impl<R: Read> Extract for Zip<R> {
-  fn extract_into(mut self: Box<Self>, path: &Path) -> Result<(), Error> {
+  fn extract_into(mut self: Box<Self>, path: &Path, limits: Limits) -> Result<(), Error> {
     let mut tmp_zip_file = tempfile().expect("Can't get a temporary file");
 
     debug!("Created a temporary zip file");
@@ -29,10 +29,19 @@ impl<R: Read> Extract for Zip<R> {
     );
 
     let mut archive = ZipArchive::new(&mut tmp_zip_file)?;
+    if archive.len() > limits.max_entries {
+      return Err(Error::TooManyEntries {
+        limit: limits.max_entries,
+      });
+    }
+    let mut budget = Budget::new(limits);
+    let root = fs::canonicalize(path)?;
 
     for i in 0..archive.len() {
       let mut file = archive.by_index(i)?;
-      let outpath = path.join(file.mangled_name());
+      budget.add_entry()?;
+      let name = file.mangled_name();
+      let outpath = path.join(&name);
 
       {
         let comment = file.comment();
@@ -41,7 +50,7 @@ impl<R: Read> Extract for Zip<R> {
         }
       }
 
-      if file.name().ends_with('/') {
+      if file.is_dir() {
         debug!(
           "File {} extracted to \"{}\"",
           i,
@@ -56,12 +65,40 @@ impl<R: Read> Extract for Zip<R> {
           file.size()
         );
         if let Some(p) = outpath.parent() {
-          if !p.exists() {
-            fs::create_dir_all(p)?;
+          fs::create_dir_all(p)?;
+          // The directory may be reached through a link of the archive, which mustn't lead outside
+          let resolved = fs::canonicalize(p)?;
+          if !resolved.starts_with(&root) {
+            return Err(Error::LinkEscape {
+              link: name.parent().unwrap_or(&name).to_path_buf(),
+              target: resolved,
+            });
+          }
+        }
+        // Never write through a file or a link extracted earlier
+        match fs::symlink_metadata(&outpath) {
+          Ok(metadata) if metadata.is_dir() => {
+            return Err(Error::FileOverDirectory { path: name });
           }
+          Ok(_) => fs::remove_file(&outpath)?,
+          Err(_) => {}
         }
-        let mut outfile = fs::File::create(&outpath)?;
-        io::copy(&mut file, &mut outfile)?;
+
+        #[cfg(unix)]
+        if file.is_symlink() {
+          let mut target = String::new();
+          (&mut file).take(4096).read_to_string(&mut target)?;
+          check_link(path, &name, Path::new(&target), false)?;
+          std::os::unix::fs::symlink(&target, &outpath)?;
+          continue;
+        }
+
+        let mut outfile = fs::OpenOptions::new()
+          .write(true)
+          .create_new(true)
+          .open(&outpath)?;
+        // The sizes recorded in a zip file can't be trusted, so the bytes written are counted
+        budget.copy(&mut file, &mut outfile)?;
       }
 
       // Get and Set permissions
@@ -70,11 +107,13 @@ impl<R: Read> Extract for Zip<R> {
         use std::os::unix::fs::PermissionsExt;
 
         if let Some(mode) = file.unix_mode() {
-          // make sure the file is executable and readable
+          // make sure the file is readable, without setuid or setgid bits
+          let mode = safe_mode(mode, file.is_dir());
           fs::set_permissions(&outpath, fs::Permissions::from_mode(mode))?;
         }
       }
     }
+    check_links(path)?;
 
     Ok(())
   }
@@ -92,9 +131,93 @@ mod tests {
     )
     .expect("Can't make request to pact v4.12.0 zip file");
     Box::new(Zip::new(response))
-      .extract_into(temp_dir.as_ref())
+      .extract_into(temp_dir.as_ref(), Limits::default())
       .expect("Can't unzip files");
     let node_file = temp_dir.as_ref().join("pact");
     assert!(node_file.exists());
   }
+
+  /// A zip file of the given entries: a path, and the data of a file or the target of a link.
+  fn zip_file(entries: &[(&str, Option<&str>, &str)]) -> Vec<u8> {
+    use std::io::Write;
+    let mut writer = zip::ZipWriter::new(io::Cursor::new(vec![]));
+    let options = zip::write::SimpleFileOptions::default();
+    for &(path, link, data) in entries {
+      if let Some(target) = link {
+        writer.add_symlink(path, target, options).unwrap();
+      } else {
+        writer.start_file(path, options).unwrap();
+        writer.write_all(data.as_bytes()).unwrap();
+      }
+    }
+    writer.finish().unwrap().into_inner()
+  }
+
+  fn extract(data: &[u8], limits: Limits) -> (tempfile::TempDir, Result<(), Error>) {
+    let dir = tempfile::tempdir().unwrap();
+    let result = Box::new(Zip::new(data)).extract_into(dir.path(), limits);
+    (dir, result)
+  }
+
+  #[test]
+  #[cfg(unix)]
+  fn test_links() {
+    let data = zip_file(&[
+      ("pact/libexec/pact", None, "pact"),
+      ("pact/bin/pact", Some("../libexec/pact"), ""),
+    ]);
+    let (dir, result) = extract(&data, Limits::default());
+    result.unwrap();
+    let binary = dir.path().join("pact/bin/pact");
+    assert!(fs::symlink_metadata(&binary).unwrap().is_symlink());
+    assert_eq!(fs::read_to_string(binary).unwrap(), "pact");
+
+    for entries in [
+      &[("pact/etc", Some("../../etc"), "")][..],
+      &[("pact/etc", Some("/etc"), "")],
+      // Writing through links that only lead outside once both are extracted
+      &[
+        ("pact/x", Some("a/../.."), ""),
+        ("pact/a", Some(".."), ""),
+        ("pact/x/outside", None, "pact"),
+      ],
+    ] {
+      let (_dir, result) = extract(&zip_file(entries), Limits::default());
+      assert!(
+        matches!(result, Err(Error::LinkEscape { .. })),
+        "{entries:?}: {result:?}"
+      );
+    }
+  }
+
+  #[test]
+  fn test_file_over_directory() {
+    let data = zip_file(&[("pact/bin/pact", None, "pact"), ("pact/bin", None, "pact")]);
+    let (_dir, result) = extract(&data, Limits::default());
+    assert!(
+      matches!(&result, Err(Error::FileOverDirectory { path }) if path == Path::new("pact/bin")),
+      "{result:?}"
+    );
+  }
+
+  #[test]
+  fn test_limits() {
+    let data = zip_file(&[("pact/a", None, "12345"), ("pact/b", None, "12345")]);
+    let (_dir, result) = extract(
+      &data,
+      Limits {
+        max_size: 9,
+        max_entries: 10,
+      },
+    );
+    assert!(matches!(result, Err(Error::TooLarge { limit: 9 })));
+    let (_dir, result) = extract(
+      &data,
+      Limits {
+        max_size: 100,
+        max_entries: 1,
+      },
+    );
+    assert!(matches!(result, Err(Error::TooManyEntries { limit: 1 })));
+  }
 }
@@ -0,0 +1,174 @@
+//! SHA-256 digests of downloaded archives, and the checksums they are verified against.
+//!
+//! Checksums are read from `SHA256SUMS`-style files, as printed by `sha256sum`:
+//! one `<hex digest>  <file name>` line per file, the file name optionally prefixed with `*`.
+
+use sha2::Digest as _;
+use std::fmt;
+use std::io::Read;
+use std::str::FromStr;
+
+/// A SHA-256 digest.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Sha256([u8; 32]);
+
+impl Sha256 {
+  /// The digest of `data`.
+  pub fn of(data: &[u8]) -> Self {
+    Self(sha2::Sha256::digest(data).into())
+  }
+}
+
+impl fmt::Display for Sha256 {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    for byte in self.0 {
+      write!(f, "{byte:02x}")?;
+    }
+    Ok(())
+  }
+}
+
+impl FromStr for Sha256 {
+  type Err = String;
+
+  /// Parses a hex digest, optionally prefixed with `sha256:` as GitHub does.
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    let hex = s.strip_prefix("sha256:").unwrap_or(s);
+    let invalid = || format!("expected 64 hexadecimal digits, got {s:?}");
+    if hex.len() != 64 || !hex.is_ascii() {
+      return Err(invalid());
+    }
+    let mut bytes = [0; 32];
+    for (byte, pair) in bytes.iter_mut().zip(hex.as_bytes().chunks_exact(2)) {
+      let pair = std::str::from_utf8(pair).map_err(|_| invalid())?;
+      *byte = u8::from_str_radix(pair, 16).map_err(|_| invalid())?;
+    }
+    Ok(Self(bytes))
+  }
+}
+
+impl serde::Serialize for Sha256 {
+  fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.collect_str(self)
+  }
+}
+
+impl<'de> serde::Deserialize<'de> for Sha256 {
+  fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+    let hex = String::deserialize(deserializer)?;
+    hex.parse().map_err(serde::de::Error::custom)
+  }
+}
+
+/// The digest an archive is expected to have, and where it comes from, e.g. `--sha256`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Checksum {
+  pub digest: Sha256,
+  pub origin: String,
+}
+
+impl Checksum {
+  pub fn new(digest: Sha256, origin: impl Into<String>) -> Self {
+    Self {
+      digest,
+      origin: origin.into(),
+    }
+  }
+}
+
+/// Whether `file_name` looks like a file listing the checksums of the other assets of a release.
+pub fn is_sums_file(file_name: &str) -> bool {
+  let lowercase = file_name.to_ascii_lowercase();
+  [
+    "sha256sums",
+    "sha256sums.txt",
+    "checksums.txt",
+    "checksums.sha256",
+  ]
+  .contains(&&lowercase[..])
+}
+
+/// Finds the digest of `file_name` in the contents of a `SHA256SUMS`-style file.
+pub fn find_in_sums(sums: &str, file_name: &str) -> Option<Sha256> {
+  sums.lines().find_map(|line| {
+    let (digest, name) = line.trim().split_once(char::is_whitespace)?;
+    let name = name.trim_start();
+    let name = name.strip_prefix('*').unwrap_or(name);
+    if name != file_name {
+      return None;
+    }
+    digest.parse().ok()
+  })
+}
+
+/// A reader hashing everything read through it.
+pub struct HashingReader<R> {
+  inner: R,
+  hasher: sha2::Sha256,
+}
+
+impl<R: Read> HashingReader<R> {
+  pub fn new(inner: R) -> Self {
+    Self {
+      inner,
+      hasher: sha2::Sha256::new(),
+    }
+  }
+
+  /// Reads what is left of the stream, and returns the digest of all of it.
+  ///
+  /// Extractors may stop before the end of the stream, e.g. at the end-of-archive marker of a tarball,
+  /// so the rest has to be read for the digest to cover the whole file.
+  pub fn finish(mut self) -> std::io::Result<Sha256> {
+    std::io::copy(&mut self, &mut std::io::sink())?;
+    Ok(Sha256(self.hasher.finalize().into()))
+  }
+}
+
+impl<R: Read> Read for HashingReader<R> {
+  fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+    let count = self.inner.read(buf)?;
+    self.hasher.update(&buf[..count]);
+    Ok(count)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use pretty_assertions::assert_eq;
+
+  const HELLO: &str = "2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824";
+
+  #[test]
+  fn test_hashing_reader() {
+    let mut reader = HashingReader::new(&b"hello"[..]);
+    let mut start = [0; 2];
+    reader.read_exact(&mut start).unwrap();
+    assert_eq!(reader.finish().unwrap().to_string(), HELLO);
+  }
+
+  #[test]
+  fn test_parse_digest() {
+    let digest: Sha256 = HELLO.parse().unwrap();
+    assert_eq!(format!("sha256:{HELLO}").parse(), Ok(digest));
+    assert_eq!(HELLO.to_uppercase().parse(), Ok(digest));
+    assert!(HELLO[1..].parse::<Sha256>().is_err());
+    assert!(format!("{}zz", &HELLO[2..]).parse::<Sha256>().is_err());
+  }
+
+  #[test]
+  fn test_find_in_sums() {
+    let sums = format!(
+      "# checksums\n{}  pact-5.0-linux-x64.tar.gz\n{HELLO} *pact-5.0-darwin-aarch64.tar.gz\n",
+      "0".repeat(64)
+    );
+    assert_eq!(
+      find_in_sums(&sums, "pact-5.0-darwin-aarch64.tar.gz"),
+      Some(HELLO.parse().unwrap())
+    );
+    assert_eq!(find_in_sums(&sums, "pact-5.0-linux-x64.zip"), None);
+    assert!(is_sums_file("SHA256SUMS"));
+    assert!(!is_sums_file("pact-5.0-linux-x64.tar.gz"));
+  }
+}
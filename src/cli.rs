@@ -73,6 +73,23 @@ pub enum SubCommand {
   /// Print the path to installed Pact version
   #[clap(name = "which", bin_name = "which")]
   Which(commands::which::Which),
+
+  /// Register a Pact build, e.g. from cabal or nix, as a version
+  ///
+  /// The build is used in place: `uninstall` only unregisters it, and never deletes it.
+  #[clap(name = "link", bin_name = "link")]
+  Link(commands::link::Link),
+
+  /// Manage the sources Pact releases are fetched from
+  ///
+  /// Sources are queried in order. When several sources publish the same version,
+  /// the first one wins.
+  #[clap(name = "source")]
+  Source(commands::source::Source),
+
+  /// Manage the downloaded archives, kept to install them again without downloading them
+  #[clap(name = "cache")]
+  Cache(commands::cache::Cache),
 }
 
 impl SubCommand {
@@ -91,6 +108,9 @@ impl SubCommand {
       Self::Uninstall(cmd) => cmd.call(config),
       Self::Unalias(cmd) => cmd.call(config),
       Self::Which(cmd) => cmd.call(config),
+      Self::Link(cmd) => cmd.call(config),
+      Self::Source(cmd) => cmd.call(config),
+      Self::Cache(cmd) => cmd.call(config),
     }
   }
 }
@@ -0,0 +1,136 @@
+use super::command::Command;
+use crate::config::PactupConfig;
+use crate::download_cache;
+use crate::lock;
+use crate::outln;
+use chrono::{DateTime, Utc};
+use colored::Colorize;
+use indicatif::HumanBytes;
+use std::time::Duration;
+use thiserror::Error;
+
+#[derive(clap::Parser, Debug)]
+pub struct Cache {
+  #[clap(subcommand)]
+  action: Action,
+}
+
+#[derive(clap::Subcommand, Debug)]
+enum Action {
+  /// List the downloaded archives
+  #[clap(name = "list", visible_aliases = &["ls"])]
+  List,
+
+  /// Print the disk space used by the downloaded archives
+  #[clap(name = "size")]
+  Size,
+
+  /// Remove all the downloaded archives, along with partial downloads
+  #[clap(name = "clean")]
+  Clean,
+
+  /// Remove the archives not installed for a while
+  ///
+  /// Example:
+  /// --------
+  /// pactup cache prune --older-than 30d
+  #[clap(name = "prune", verbatim_doc_comment)]
+  Prune {
+    /// How long an archive is kept after it was last installed, e.g. `12h`, `30d` or `8w`.
+    #[clap(long, value_name = "DURATION", value_parser = parse_duration)]
+    older_than: Duration,
+  },
+}
+
+impl Command for Cache {
+  type Error = Error;
+
+  fn apply(self, config: &PactupConfig) -> Result<(), Self::Error> {
+    let downloads_dir = config.downloads_dir();
+    match self.action {
+      Action::List => {
+        for entry in download_cache::list(&downloads_dir)? {
+          let file_name = entry.url.path_segments().and_then(Iterator::last);
+          let last_used = DateTime::<Utc>::from(entry.last_used).format("%Y-%m-%d");
+          println!(
+            "* {} {}",
+            file_name.unwrap_or_default().cyan(),
+            format!("({}, last used {last_used})", HumanBytes(entry.size)).dimmed()
+          );
+          println!("  {}", entry.url.as_str().dimmed());
+        }
+      }
+      Action::Size => println!("{}", HumanBytes(download_cache::size(&downloads_dir))),
+      Action::Clean => {
+        let _lock = lock::lock_store(lock::Mode::Exclusive, config)
+          .map_err(|source| Error::CantLock { source })?;
+        let size = download_cache::size(&downloads_dir);
+        download_cache::clean(&downloads_dir)?;
+        outln!(
+          config,
+          Info,
+          "Removed the downloaded archives ({})",
+          HumanBytes(size)
+        );
+      }
+      Action::Prune { older_than } => {
+        let _lock = lock::lock_store(lock::Mode::Exclusive, config)
+          .map_err(|source| Error::CantLock { source })?;
+        let removed = download_cache::prune(&downloads_dir, older_than)?;
+        let size: u64 = removed.iter().map(|entry| entry.size).sum();
+        outln!(
+          config,
+          Info,
+          "Removed {} archive(s) ({})",
+          removed.len(),
+          HumanBytes(size)
+        );
+      }
+    }
+    Ok(())
+  }
+}
+
+/// Parses a duration such as `90m`, `12h`, `30d` or `8w`.
+fn parse_duration(value: &str) -> Result<Duration, String> {
+  let invalid = || format!("expected a number followed by m, h, d or w, e.g. `30d`, got {value:?}");
+  let unit = value.chars().last().ok_or_else(invalid)?;
+  let seconds = match unit {
+    'm' => 60,
+    'h' => 60 * 60,
+    'd' => 24 * 60 * 60,
+    'w' => 7 * 24 * 60 * 60,
+    _ => return Err(invalid()),
+  };
+  let count: u64 = value[..value.len() - 1].parse().map_err(|_| invalid())?;
+  let seconds = count.checked_mul(seconds).ok_or_else(invalid)?;
+  Ok(Duration::from_secs(seconds))
+}
+
+#[derive(Debug, Error)]
+pub enum Error {
+  #[error("Can't read the download cache: {source}")]
+  IoError {
+    #[from]
+    source: std::io::Error,
+  },
+  #[error("Can't lock the installations directory: {source}")]
+  CantLock { source: std::io::Error },
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use pretty_assertions::assert_eq;
+
+  #[test]
+  fn test_parse_duration() {
+    assert_eq!(parse_duration("90m"), Ok(Duration::from_secs(90 * 60)));
+    assert_eq!(parse_duration("30d"), Ok(Duration::from_secs(30 * 86400)));
+    assert_eq!(parse_duration("2w"), Ok(Duration::from_secs(14 * 86400)));
+    assert!(parse_duration("30").is_err());
+    assert!(parse_duration("40000000000000w").is_err());
+    assert!(parse_duration("d").is_err());
+    assert!(parse_duration("").is_err());
+  }
+}
@@ -1,28 +1,46 @@
 use super::command::Command;
 use crate::alias::create_alias;
+use crate::archive::Archive;
+use crate::checksum::{self, Checksum, Sha256};
 use crate::config::PactupConfig;
-use crate::downloader::{install_pact_dist, Error as DownloaderError};
+use crate::download_cache;
+use crate::downloader::{
+  install_pact_archive, install_pact_dist, Error as DownloaderError, Integrity,
+};
+use crate::installed_versions::{self, Manifest};
+use crate::lock::{self, Lock};
 use crate::outln;
 use crate::progress::ProgressConfig;
-use crate::remote_pact_index::{self, Release};
+use crate::remote_pact_index::{self, Asset, AssetTarget, Rejection, Release};
+use crate::signature::{self, Signature};
+use crate::transfer::{self, Credentials, Transfer};
 use crate::user_version::UserVersion;
 use crate::version::Version;
 use crate::version_files::get_user_version_for_directory;
 use colored::Colorize;
+use indicatif::{HumanBytes, MultiProgress};
 use log::debug;
+use std::collections::BTreeSet;
+use std::io::Read;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
 use thiserror::Error;
+use url::Url;
 
 #[derive(clap::Parser, Debug, Default)]
+#[allow(clippy::struct_excessive_bools)]
+#[clap(group(clap::ArgGroup::new("custom_archive").args(["archive", "url"])))]
 pub struct Install {
-  /// A version string. Can be a partial semver or a 'development' version.
-  pub version: Option<UserVersion>,
+  /// The versions to install. Each can be a partial semver or a 'development' version.
+  pub versions: Vec<UserVersion>,
 
   /// Install latest nightly version.
-  #[clap(long, conflicts_with_all = &["version", "latest"])]
+  #[clap(long, conflicts_with_all = &["versions", "latest"])]
   pub nightly: bool,
 
   /// Install latest version.
-  #[clap(long, conflicts_with_all = &["version", "nightly"])]
+  #[clap(long, conflicts_with_all = &["versions", "nightly"])]
   pub latest: bool,
 
   /// Show an interactive progress bar for the download status.
@@ -31,51 +49,157 @@ pub struct Install {
   pub progress: ProgressConfig,
 
   /// Force install even if the version is already installed.
+  /// The installed version is only replaced once the new one is ready.
   #[clap(long)]
   pub force: bool,
+
+  /// Install a local archive instead of a release, or `-` to read it from stdin.
+  /// Requires `--as`.
+  #[clap(
+    long,
+    value_name = "PATH",
+    conflicts_with_all = &["versions", "nightly", "latest", "url"],
+    requires = "label"
+  )]
+  pub archive: Option<PathBuf>,
+
+  /// Install the archive at this URL instead of a release. Requires `--as`.
+  #[clap(
+    long,
+    conflicts_with_all = &["versions", "nightly", "latest"],
+    requires = "label"
+  )]
+  pub url: Option<Url>,
+
+  /// The version to install an archive as, with `--archive` or `--url`, e.g. `5.1.0-rc`.
+  #[clap(
+    long = "as",
+    id = "label",
+    value_name = "VERSION",
+    requires = "custom_archive"
+  )]
+  pub label: Option<Version>,
+
+  /// The type of the archive (`tar.gz`, `tar.xz`, `tar.zst`, `tar.bz2` or `zip`), when it can't be
+  /// told from its contents nor its name.
+  #[clap(long, value_name = "FORMAT")]
+  pub archive_format: Option<Archive>,
+
+  /// The expected SHA-256 digest of the archive. The installation fails if the download doesn't match.
+  /// Only a single version can be installed with it; use `--lockfile` for several.
+  #[clap(long, value_name = "DIGEST")]
+  pub sha256: Option<Sha256>,
+
+  /// A `SHA256SUMS`-style file pinning the digests of archives, e.g. committed along with a project.
+  /// Archives it doesn't list are checked against the checksums published with the release.
+  #[clap(long, value_name = "PATH")]
+  pub lockfile: Option<PathBuf>,
+
+  /// Install even if the archive isn't signed, or its signature can't be verified,
+  /// although the source pins public keys.
+  #[clap(long)]
+  pub allow_unsigned: bool,
+
+  /// Download large archives in this many segments at once, when the server supports it.
+  #[clap(long, value_name = "N", default_value_t = 1, value_parser = clap::value_parser!(u8).range(1..=16))]
+  pub connections: u8,
+
+  /// How many versions to download and extract at once, when installing several.
+  #[clap(long, short = 'j', value_name = "N", default_value_t = 4, value_parser = clap::value_parser!(u8).range(1..=16))]
+  pub jobs: u8,
 }
 
 impl Install {
-  fn resolve_version(&self) -> Result<Option<UserVersion>, Error> {
-    match (self.version.as_ref(), self.nightly, self.latest) {
-      (Some(v), false, false) => Ok(Some(v.clone())),
-      (None, true, false) => Ok(Some(UserVersion::Full(Version::Nightly(
+  /// How archives are downloaded into the cache.
+  fn transfer(&self, config: &PactupConfig) -> Transfer {
+    Transfer {
+      downloads_dir: config.downloads_dir(),
+      connections: usize::from(self.connections.max(1)),
+      retries: config.http_settings().retries,
+      show_progress: self.progress.enabled(config),
+      bars: None,
+      offline: config.offline,
+      credentials: config.github_token().map(|token| Credentials {
+        api_url: config.github_api_url.clone(),
+        token,
+      }),
+    }
+  }
+
+  /// Where to download `asset` from, see [`Asset::fetch_url`].
+  fn asset_url<'a>(asset: &'a Asset, config: &PactupConfig) -> &'a Url {
+    asset.fetch_url(config.github_token().is_some())
+  }
+
+  fn resolve_versions(&self) -> Result<Vec<UserVersion>, Error> {
+    if self.sha256.is_some() && self.versions.len() > 1 {
+      return Err(Error::Sha256ForSeveralVersions);
+    }
+    match (&self.versions[..], self.nightly, self.latest) {
+      (versions, false, false) => Ok(versions.to_vec()),
+      ([], true, false) => Ok(vec![UserVersion::Full(Version::Nightly(
         "nightly".to_string(),
-      )))),
-      (None, false, true) => Ok(Some(UserVersion::Full(Version::Latest))),
-      (None, false, false) => Ok(None),
+      ))]),
+      ([], false, true) => Ok(vec![UserVersion::Full(Version::Latest)]),
       _ => Err(Error::TooManyVersionsProvided),
     }
   }
 
+  /// Resolves the release of each requested version, listing the releases once for all of them.
+  fn resolve_releases(
+    requested_versions: &[UserVersion],
+    config: &PactupConfig,
+  ) -> Result<Vec<Result<Release, Error>>, Error> {
+    let installable = |version: &UserVersion| {
+      !matches!(
+        version,
+        UserVersion::Full(Version::Bypassed | Version::Alias(_))
+      )
+    };
+    let releases = if requested_versions.iter().any(installable) {
+      let majors: BTreeSet<_> = requested_versions.iter().map(UserVersion::major).collect();
+      let major = if majors.len() == 1 {
+        majors.into_iter().next().flatten()
+      } else {
+        None
+      };
+      Self::get_available_releases(major, config)?
+    } else {
+      vec![]
+    };
+    Ok(
+      requested_versions
+        .iter()
+        .map(|version| Self::resolve_release(version, &releases, config))
+        .collect(),
+    )
+  }
+
   fn resolve_release(
     current_version: &UserVersion,
+    releases: &[Release],
     config: &PactupConfig,
   ) -> Result<Release, Error> {
     match current_version {
       UserVersion::Full(Version::Semver(actual_version)) => {
-        Self::resolve_semver_release(actual_version, current_version, config)
+        Self::resolve_semver_release(actual_version, current_version, releases, config)
       }
       UserVersion::Full(v @ (Version::Bypassed | Version::Alias(_))) => {
         Err(Error::UninstallableVersion { version: v.clone() })
       }
-      UserVersion::Full(Version::Nightly(tag)) => Self::resolve_nightly_release(tag, config),
-      UserVersion::Full(Version::Latest) => Self::resolve_latest_release(config),
-      _ => Self::resolve_generic_release(current_version, config),
+      UserVersion::Full(Version::Nightly(tag)) => Self::resolve_nightly_release(tag, releases),
+      UserVersion::Full(Version::Latest) => Self::resolve_latest_release(releases),
+      _ => Self::resolve_generic_release(current_version, releases, config),
     }
   }
 
   fn resolve_semver_release(
     actual_version: &node_semver::Version,
     current_version: &UserVersion,
+    releases: &[Release],
     config: &PactupConfig,
   ) -> Result<Release, Error> {
-    let available_releases = Self::get_available_releases(config)?;
-    let release = current_version
-      .to_release(&available_releases, config)
-      .ok_or_else(|| Error::CantFindPactVersion {
-        requested_version: current_version.clone(),
-      })?;
+    let release = Self::resolve_generic_release(current_version, releases, config)?;
 
     debug!(
       "Resolved {} into Pact version {}",
@@ -83,61 +207,94 @@ impl Install {
       release.tag.v_str().cyan()
     );
 
-    Ok(release.clone())
+    Ok(release)
   }
 
-  fn resolve_nightly_release(nightly_tag: &str, config: &PactupConfig) -> Result<Release, Error> {
-    let release = remote_pact_index::get_by_tag(config.repo_urls(), nightly_tag).map_err(|_| {
-      Error::CantFindNightly {
+  fn resolve_nightly_release(nightly_tag: &str, releases: &[Release]) -> Result<Release, Error> {
+    let release = releases
+      .iter()
+      .find(|x| x.tag.to_string() == nightly_tag)
+      .ok_or_else(|| Error::CantFindNightly {
         nightly_tag: nightly_tag.to_string(),
-      }
-    })?;
+      })?;
 
     debug!(
       "Resolved nightly into Pact version {}",
       release.tag.v_str().cyan()
     );
 
-    Ok(release)
+    Ok(release.clone())
   }
 
-  fn resolve_latest_release(config: &PactupConfig) -> Result<Release, Error> {
-    let release =
-      remote_pact_index::latest(config.repo_urls()).map_err(|_| Error::CantFindLatest)?;
+  fn resolve_latest_release(releases: &[Release]) -> Result<Release, Error> {
+    let release = remote_pact_index::pick_latest(releases).ok_or(Error::CantFindLatest)?;
 
     debug!(
       "Resolved latest into Pact version {}",
       release.tag.v_str().cyan()
     );
 
-    Ok(release)
+    Ok(release.clone())
   }
 
+  /// Resolves `current_version` among the releases of the sources serving its major version.
   fn resolve_generic_release(
     current_version: &UserVersion,
+    releases: &[Release],
     config: &PactupConfig,
   ) -> Result<Release, Error> {
-    let available_releases = Self::get_available_releases(config)?;
+    let major = current_version.major();
+    let served = releases.iter().filter(|release| {
+      release
+        .source
+        .as_ref()
+        .is_none_or(|source| source.serves(major))
+    });
     current_version
-      .to_release(&available_releases, config)
+      .to_release(served, config)
       .ok_or_else(|| Error::CantFindPactVersion {
         requested_version: current_version.clone(),
       })
       .cloned()
   }
 
-  fn get_available_releases(config: &PactupConfig) -> Result<Vec<Release>, Error> {
-    remote_pact_index::list(config.repo_urls())
+  /// Lists the releases of the sources that may publish `major`, or of all of them when `None`.
+  fn get_available_releases(
+    major: Option<u64>,
+    config: &PactupConfig,
+  ) -> Result<Vec<Release>, Error> {
+    remote_pact_index::list_for_major(config, major)
       .map_err(|source| Error::CantListRemoteVersions { source })
   }
 
+  /// The installed version matching the user input, used to avoid resolving releases when offline.
+  fn find_installed_version(
+    current_version: &UserVersion,
+    config: &PactupConfig,
+  ) -> Result<Option<Version>, Error> {
+    let installed_versions = installed_versions::list(config.installations_dir())
+      .map_err(|source| Error::CantListLocalVersions { source })?;
+    Ok(
+      current_version
+        .to_version(&installed_versions, config)
+        .cloned(),
+    )
+  }
+
   fn handle_installation(
     &self,
     release: &Release,
     current_version: &UserVersion,
     config: &PactupConfig,
   ) -> Result<(), Error> {
-    let version = &release.tag;
+    Self::announce(&release.tag, config);
+    self.install_release(release, current_version, &self.transfer(config), config)?;
+    Self::handle_aliases(release, current_version, config)?;
+
+    Ok(())
+  }
+
+  fn announce(version: &Version, config: &PactupConfig) {
     outln!(
       config,
       Info,
@@ -145,16 +302,292 @@ impl Install {
       format!("Pact {version}",).cyan(),
       config.arch.as_str()
     );
+  }
 
-    let download_url = release
-      .download_url()
-      .ok_or_else(|| Error::CantFindReleaseAsset {
-        requested_version: current_version.clone(),
+  /// Downloads and installs the asset of `release` for the current platform, leaving aliases aside.
+  fn install_release(
+    &self,
+    release: &Release,
+    current_version: &UserVersion,
+    transfer: &Transfer,
+    config: &PactupConfig,
+  ) -> Result<(), Error> {
+    let version = &release.tag;
+    let _locks = Self::lock(version, config)?;
+
+    let target = AssetTarget::current(config);
+    let asset = release
+      .asset_for(&target)
+      .ok_or_else(|| Self::missing_asset_error(release, current_version, &target))?;
+
+    if let Some(size) = asset.size {
+      Self::check_disk_space(size, config)?;
+    }
+
+    let checksum = self.expected_checksum(release, asset, config)?;
+    let signature = self.expected_signature(release, asset, config)?;
+    let manifest = Manifest {
+      source: release.source.as_ref().map(|source| source.name.clone()),
+      variant: asset
+        .file_name()
+        .and_then(|file_name| release.naming().parse(file_name).ok())
+        .and_then(|name| name.variant()),
+      ..Manifest::default()
+    };
+    self.perform_installation(
+      version,
+      Self::asset_url(asset, config),
+      manifest,
+      Integrity {
+        checksum: checksum.as_ref(),
+        signature: signature.as_ref(),
+      },
+      transfer,
+      config,
+    )
+  }
+
+  /// Installs several versions, `--jobs` at a time, then sets their aliases in the order they were
+  /// requested and tells how the installation of each went.
+  fn install_several(
+    &self,
+    requested_versions: &[UserVersion],
+    config: &PactupConfig,
+  ) -> Result<(), Error> {
+    let resolved = Self::resolve_releases(requested_versions, config)?;
+    // Several requested versions may resolve to the same release, e.g. `5` and `5.1`.
+    let mut releases: Vec<(&UserVersion, &Release)> = vec![];
+    for (requested, release) in requested_versions.iter().zip(&resolved) {
+      if let Ok(release) = release {
+        if !releases.iter().any(|(_, x)| x.tag == release.tag) {
+          releases.push((requested, release));
+        }
+      }
+    }
+
+    for (_, release) in &releases {
+      Self::announce(&release.tag, config);
+    }
+    let transfer = Transfer {
+      bars: Some(MultiProgress::new()),
+      ..self.transfer(config)
+    };
+    let jobs = usize::from(self.jobs.max(1));
+    let installed = parallel_map(&releases, jobs, |(requested, release)| {
+      self.install_release(release, requested, &transfer, config)
+    });
+
+    let mut failed = 0;
+    for (requested, resolved) in requested_versions.iter().zip(&resolved) {
+      let outcome = resolved
+        .as_ref()
+        .map_err(ToString::to_string)
+        .and_then(|release| {
+          let index = releases
+            .iter()
+            .position(|(_, x)| x.tag == release.tag)
+            .expect("Every resolved release is installed");
+          installed[index].as_ref().map_err(ToString::to_string)?;
+          Self::handle_aliases(release, requested, config).map_err(|err| err.to_string())?;
+          Ok(&release.tag)
+        });
+      match outcome {
+        Ok(version) => outln!(
+          config,
+          Info,
+          "{} {} {}",
+          "✓".green(),
+          format!("Pact {version}").cyan(),
+          format!("({requested})").dimmed()
+        ),
+        Err(err) => {
+          failed += 1;
+          outln!(config, Error, "{} {}: {}", "✗".red(), requested, err);
+        }
+      }
+    }
+    if failed > 0 {
+      return Err(Error::SomeInstallationsFailed {
+        failed,
+        total: requested_versions.len(),
+      });
+    }
+    Ok(())
+  }
+
+  /// The checksum to verify `asset` against: the pinned one, or else the one published by the
+  /// provider index, or else the one listed in a `SHA256SUMS`-style asset of the release.
+  fn expected_checksum(
+    &self,
+    release: &Release,
+    asset: &Asset,
+    config: &PactupConfig,
+  ) -> Result<Option<Checksum>, Error> {
+    let file_name = asset.file_name().unwrap_or_default();
+    if let Some(checksum) = self.pinned_checksum(file_name)? {
+      return Ok(Some(checksum));
+    }
+    if let Some(digest) = asset.sha256 {
+      return Ok(Some(Checksum::new(digest, "the release index")));
+    }
+
+    let sums_assets = release
+      .assets
+      .iter()
+      .filter(|x| x.file_name().is_some_and(checksum::is_sums_file))
+      .filter(|x| {
+        !config.offline
+          || x.download_url.scheme() == "file"
+          || download_cache::contains(&config.downloads_dir(), Self::asset_url(x, config))
+      });
+    for sums_asset in sums_assets {
+      debug!(
+        "Looking for the checksum of {} in {}",
+        file_name, sums_asset.download_url
+      );
+      let url = Self::asset_url(sums_asset, config);
+      let path = self
+        .fetch_asset(url, config)
+        .map_err(|source| Error::CantFetchChecksums {
+          url: url.clone(),
+          source,
+        })?;
+      let sums = std::fs::read_to_string(path)?;
+      if let Some(digest) = checksum::find_in_sums(&sums, file_name) {
+        let origin = sums_asset.file_name().unwrap_or_default();
+        return Ok(Some(Checksum::new(digest, origin)));
+      }
+    }
+
+    debug!(
+      "No checksum published for {}, it can't be verified",
+      file_name
+    );
+    Ok(None)
+  }
+
+  /// The signature of `asset`, when its source pins the public keys its assets are signed with.
+  fn expected_signature(
+    &self,
+    release: &Release,
+    asset: &Asset,
+    config: &PactupConfig,
+  ) -> Result<Option<Signature>, Error> {
+    let Some(source) = release
+      .source
+      .as_ref()
+      .filter(|source| !source.public_keys.is_empty())
+    else {
+      return Ok(None);
+    };
+    let file_name = asset.file_name().unwrap_or_default();
+    if self.allow_unsigned {
+      outln!(
+        config,
+        Error,
+        "{} not verifying the signature of {}, as `--allow-unsigned` was given",
+        "warning:".yellow().bold(),
+        file_name
+      );
+      return Ok(None);
+    }
+
+    let signature_name = signature::file_name_for(file_name);
+    let signature_asset = release
+      .assets
+      .iter()
+      .find(|x| x.file_name() == Some(&signature_name))
+      .ok_or_else(|| Error::Unsigned {
+        file_name: file_name.to_string(),
+        source_name: source.name.clone(),
+      })?;
+    debug!("Fetching the signature {}", signature_asset.download_url);
+    let url = Self::asset_url(signature_asset, config);
+    let path = self
+      .fetch_asset(url, config)
+      .map_err(|source| Error::CantFetchSignature {
+        url: url.clone(),
+        source,
       })?;
+    let text = std::fs::read_to_string(path)?;
+    Ok(Some(Signature::decode(
+      &text,
+      &source.public_keys,
+      signature_name,
+    )?))
+  }
 
-    self.perform_installation(version, &download_url, config)?;
-    Self::handle_aliases(release, current_version, config)?;
+  /// The path of a small asset of a release, such as a checksums file or a signature. Local files
+  /// are read where they are, the others are kept in the download cache, to be read again offline.
+  fn fetch_asset(&self, url: &Url, config: &PactupConfig) -> Result<PathBuf, transfer::Error> {
+    match url.to_file_path() {
+      Ok(path) if url.scheme() == "file" => Ok(path),
+      _ => Transfer {
+        show_progress: false,
+        ..self.transfer(config)
+      }
+      .fetch(url, None),
+    }
+  }
+
+  /// The checksum given with `--sha256`, or pinned for `file_name` in the `--lockfile`.
+  fn pinned_checksum(&self, file_name: &str) -> Result<Option<Checksum>, Error> {
+    if let Some(digest) = self.sha256 {
+      return Ok(Some(Checksum::new(digest, "--sha256")));
+    }
+    let Some(lockfile) = &self.lockfile else {
+      return Ok(None);
+    };
+    let sums = std::fs::read_to_string(lockfile).map_err(|source| Error::CantReadLockfile {
+      path: lockfile.clone(),
+      source,
+    })?;
+    Ok(
+      checksum::find_in_sums(&sums, file_name)
+        .map(|digest| Checksum::new(digest, lockfile.display().to_string())),
+    )
+  }
+
+  /// Explains why no asset fits, when the reason is more specific than a missing build.
+  fn missing_asset_error(
+    release: &Release,
+    current_version: &UserVersion,
+    target: &AssetTarget,
+  ) -> Error {
+    let only_glibc_builds = release
+      .assess_assets(target)
+      .iter()
+      .any(|(_, verdict)| verdict.as_ref().err() == Some(&Rejection::GlibcOnly));
+    if only_glibc_builds {
+      Error::OnlyGlibcBuilds {
+        version: release.tag.clone(),
+      }
+    } else {
+      Error::CantFindReleaseAsset {
+        requested_version: current_version.clone(),
+      }
+    }
+  }
 
+  /// Makes sure the archive, whose size is known upfront, fits on the disk before downloading it.
+  fn check_disk_space(required: u64, config: &PactupConfig) -> Result<(), Error> {
+    let installations_dir = config.installations_dir();
+    let Some(available) = crate::fs::available_space(&installations_dir) else {
+      debug!("Can't find the free space of {:?}", installations_dir);
+      return Ok(());
+    };
+    debug!(
+      "{} required, {} available",
+      HumanBytes(required),
+      HumanBytes(available)
+    );
+    if available < required {
+      return Err(Error::NotEnoughDiskSpace {
+        path: installations_dir,
+        required,
+        available,
+      });
+    }
     Ok(())
   }
 
@@ -162,23 +595,155 @@ impl Install {
     &self,
     version: &Version,
     download_url: &url::Url,
+    manifest: Manifest,
+    integrity: Integrity<'_>,
+    transfer: &Transfer,
     config: &PactupConfig,
   ) -> Result<(), Error> {
-    match install_pact_dist(
+    if config.offline
+      && download_url.scheme() != "file"
+      && !download_cache::contains(&config.downloads_dir(), download_url)
+    {
+      return Err(Error::CantDownloadOffline {
+        version: version.clone(),
+      });
+    }
+
+    let result = install_pact_dist(
       version,
       download_url,
       config.installations_dir(),
       config.arch,
-      self.progress.enabled(config),
+      transfer,
       self.force,
-    ) {
+      integrity,
+      manifest,
+    );
+    Self::check_installed(result, config).map_err(|source| Error::DownloadError { source })?;
+    Ok(())
+  }
+
+  /// Locks the store and `version` for the installation, waiting for the processes using them.
+  /// A version installed meanwhile by another process is then found already installed.
+  fn lock(version: &Version, config: &PactupConfig) -> Result<(Lock, Lock), Error> {
+    let store =
+      lock::lock_store(lock::Mode::Shared, config).map_err(|source| Error::CantLock { source })?;
+    let version =
+      lock::lock_version(version, config).map_err(|source| Error::CantLock { source })?;
+    Ok((store, version))
+  }
+
+  /// Fails unless the version was installed, or was already there, which is only worth a warning.
+  fn check_installed(
+    result: Result<(), DownloaderError>,
+    config: &PactupConfig,
+  ) -> Result<(), DownloaderError> {
+    match result {
       Err(err @ DownloaderError::VersionAlreadyInstalled { .. }) => {
         outln!(config, Error, "{} {}", "warning:".bold().yellow(), err);
         Ok(())
       }
-      Err(source) => Err(Error::DownloadError { source }),
-      Ok(()) => Ok(()),
+      result => result,
+    }
+  }
+
+  /// Installs the archive given with `--archive` or `--url` as the `label` version.
+  fn install_archive(&self, label: &Version, config: &PactupConfig) -> Result<(), Error> {
+    if !label.is_installable() {
+      return Err(Error::InvalidArchiveVersion {
+        version: label.clone(),
+      });
+    }
+    let _locks = Self::lock(label, config)?;
+
+    let mut cached = None;
+    let (reader, name): (Box<dyn Read>, String) = match (&self.archive, &self.url) {
+      (Some(path), _) if path.as_os_str() == "-" => {
+        (Box::new(std::io::stdin().lock()), "stdin".to_string())
+      }
+      (Some(path), _) => {
+        let file = std::fs::File::open(path).map_err(|source| Error::CantReadArchive {
+          path: path.clone(),
+          source,
+        })?;
+        (Box::new(file), path.display().to_string())
+      }
+      (None, Some(url)) => {
+        let path = match url.to_file_path() {
+          Ok(path) if url.scheme() == "file" => path,
+          _ => {
+            if config.offline && !download_cache::contains(&config.downloads_dir(), url) {
+              return Err(Error::CantDownloadOffline {
+                version: label.clone(),
+              });
+            }
+            let file_name = url
+              .path_segments()
+              .and_then(Iterator::last)
+              .unwrap_or_default();
+            let expected = self.pinned_checksum(file_name)?;
+            let path = self
+              .transfer(config)
+              .fetch(url, expected.as_ref().map(|x| &x.digest))
+              .map_err(|source| Error::DownloadError {
+                source: source.into(),
+              })?;
+            cached = Some(path.clone());
+            path
+          }
+        };
+        let file = std::fs::File::open(&path).map_err(|source| Error::CantReadArchive {
+          path: path.clone(),
+          source,
+        })?;
+        (Box::new(file), url.path().to_string())
+      }
+      (None, None) => unreachable!("an archive or a URL is required to install an archive"),
+    };
+    let (sniffed, reader) = Archive::sniff(reader).map_err(|source| Error::CantReadArchive {
+      path: PathBuf::from(&name),
+      source,
+    })?;
+    let format = self
+      .archive_format
+      .or(sniffed)
+      .or_else(|| Archive::from_url(&name))
+      .ok_or_else(|| Error::UnknownArchiveFormat { name: name.clone() })?;
+
+    let file_name = name.rsplit(['/', '\\']).next().unwrap_or_default();
+    let expected = self.pinned_checksum(file_name)?;
+
+    outln!(
+      config,
+      Info,
+      "Installing {} from {}",
+      format!("Pact {label}").cyan(),
+      name
+    );
+    let result = install_pact_archive(
+      label,
+      reader,
+      format,
+      config.installations_dir(),
+      config.arch,
+      self.force,
+      expected.as_ref(),
+      Manifest {
+        asset_url: self.url.clone(),
+        ..Manifest::default()
+      },
+    );
+    if let Some(path) = cached.filter(|_| {
+      matches!(
+        result,
+        Err(DownloaderError::ChecksumMismatch { .. } | DownloaderError::CantExtractFile { .. })
+      )
+    }) {
+      download_cache::remove(&path);
     }
+    Self::check_installed(result, config).map_err(|source| Error::CantInstallArchive { source })?;
+    Self::tag_default(label, config)?;
+    Ok(())
   }
 
   fn handle_aliases(
@@ -186,13 +751,7 @@ impl Install {
     current_version: &UserVersion,
     config: &PactupConfig,
   ) -> Result<(), Error> {
-    if !config.default_version_dir().exists() {
-      debug!(
-        "Tagging {} as the default version",
-        release.tag.v_str().cyan()
-      );
-      create_alias(config, "default", &release.tag)?;
-    }
+    Self::tag_default(&release.tag, config)?;
 
     if let Some(tagged_alias) = current_version.inferred_alias() {
       tag_alias(config, &release.tag, &tagged_alias)?;
@@ -202,21 +761,85 @@ impl Install {
   }
 }
 
+impl Install {
+  /// Makes `version` the default version, unless there is one already.
+  fn tag_default(version: &Version, config: &PactupConfig) -> Result<(), Error> {
+    if !config.default_version_dir().exists() {
+      debug!("Tagging {} as the default version", version.v_str().cyan());
+      create_alias(config, "default", version)?;
+    }
+    Ok(())
+  }
+}
+
 impl Command for Install {
   type Error = Error;
 
   fn apply(self, config: &PactupConfig) -> Result<(), Self::Error> {
-    let current_dir = std::env::current_dir()?;
-    let current_version = self
-      .resolve_version()?
-      .or_else(|| get_user_version_for_directory(&current_dir, config))
-      .ok_or(Error::CantInferVersion)?;
+    if let Some(label) = &self.label {
+      return self.install_archive(label, config);
+    }
 
-    let release = Self::resolve_release(&current_version, config)?;
-    self.handle_installation(&release, &current_version, config)
+    let mut requested_versions = self.resolve_versions()?;
+    if requested_versions.is_empty() {
+      let current_dir = std::env::current_dir()?;
+      let current_version =
+        get_user_version_for_directory(&current_dir, config).ok_or(Error::CantInferVersion)?;
+      requested_versions.push(current_version);
+    }
+
+    if config.offline && !self.force {
+      let mut missing_versions = vec![];
+      for current_version in requested_versions {
+        if let Some(version) = Self::find_installed_version(&current_version, config)? {
+          outln!(
+            config,
+            Info,
+            "Pact {} is already installed, nothing to do while offline",
+            version.v_str().cyan()
+          );
+        } else {
+          missing_versions.push(current_version);
+        }
+      }
+      requested_versions = missing_versions;
+    }
+
+    match &requested_versions[..] {
+      [] => Ok(()),
+      [current_version] => {
+        let release = Self::resolve_releases(&requested_versions, config)?.remove(0)?;
+        self.handle_installation(&release, current_version, config)
+      }
+      _ => self.install_several(&requested_versions, config),
+    }
   }
 }
 
+/// Calls `f` on each item, `jobs` items at a time, and returns the results in order.
+fn parallel_map<T: Sync, R: Send>(items: &[T], jobs: usize, f: impl Fn(&T) -> R + Sync) -> Vec<R> {
+  let next = AtomicUsize::new(0);
+  let results = Mutex::new(items.iter().map(|_| None).collect::<Vec<_>>());
+  std::thread::scope(|scope| {
+    for _ in 0..jobs.min(items.len()) {
+      scope.spawn(|| loop {
+        let index = next.fetch_add(1, Ordering::Relaxed);
+        let Some(item) = items.get(index) else {
+          break;
+        };
+        let result = f(item);
+        results.lock().expect("A job panicked")[index] = Some(result);
+      });
+    }
+  });
+  results
+    .into_inner()
+    .expect("A job panicked")
+    .into_iter()
+    .map(|result| result.expect("Every item is processed"))
+    .collect()
+}
+
 fn tag_alias(
   config: &PactupConfig,
   matched_version: &Version,
@@ -245,10 +868,22 @@ pub enum Error {
   CantInferVersion,
   #[error(transparent)]
   CantListRemoteVersions { source: remote_pact_index::Error },
+  #[error("Can't list locally installed versions: {}", source)]
+  CantListLocalVersions { source: installed_versions::Error },
+  #[error("Can't download Pact {} while offline. Run the command again without `--offline`.", version.v_str())]
+  CantDownloadOffline { version: Version },
   #[error("Can't find a Pact version that matches {requested_version} in remote")]
   CantFindPactVersion { requested_version: UserVersion },
+  #[error("Not enough disk space in {}: {} required, but only {} available", path.display(), HumanBytes(*required), HumanBytes(*available))]
+  NotEnoughDiskSpace {
+    path: std::path::PathBuf,
+    required: u64,
+    available: u64,
+  },
   #[error("Can't find a release asset for the requested version: {requested_version}")]
   CantFindReleaseAsset { requested_version: UserVersion },
+  #[error("Pact {} only ships Linux binaries linked against glibc, which can't run on musl systems such as Alpine.\nInstall a glibc compatibility layer (e.g. `apk add gcompat`) and run the command again with `--arch x64`.", version.v_str())]
+  OnlyGlibcBuilds { version: Version },
   #[error("Can't find nightly version named {nightly_tag}")]
   CantFindNightly { nightly_tag: String },
   #[error("Can't find any versions in the upstream version index.")]
@@ -257,11 +892,48 @@ pub enum Error {
   UninstallableVersion { version: Version },
   #[error("Too many versions provided. Please don't use --nightly with a version string.")]
   TooManyVersionsProvided,
+  #[error("Can't install an archive as {}: use a version number such as `5.1.0-rc`, or a nightly name.", version.v_str())]
+  InvalidArchiveVersion { version: Version },
+  #[error("Can't read the archive {}: {source}", path.display())]
+  CantReadArchive {
+    path: PathBuf,
+    source: std::io::Error,
+  },
+  #[error("Can't install the archive: {source}")]
+  CantInstallArchive { source: DownloaderError },
+  #[error("Can't tell the type of the archive {name}. Use `--archive-format` to set it.")]
+  UnknownArchiveFormat { name: String },
+  #[error("Can't read the lockfile {}: {source}", path.display())]
+  CantReadLockfile {
+    path: PathBuf,
+    source: std::io::Error,
+  },
+  #[error("{file_name} isn't signed, but {source_name} pins the public keys its assets are signed with.\nUse `--allow-unsigned` to install it anyway.")]
+  Unsigned {
+    file_name: String,
+    source_name: String,
+  },
+  #[error("Can't fetch the signature at {url}: {source}")]
+  CantFetchSignature { url: Url, source: transfer::Error },
+  #[error("Can't verify the signature of the release asset: {source}")]
+  InvalidSignature {
+    #[from]
+    source: signature::Error,
+  },
+  #[error("Can't fetch the checksums at {url}: {source}")]
+  CantFetchChecksums { url: Url, source: transfer::Error },
+  #[error("Can't lock the installations directory: {source}")]
+  CantLock { source: std::io::Error },
+  #[error("{failed} of the {total} requested versions couldn't be installed")]
+  SomeInstallationsFailed { failed: usize, total: usize },
+  #[error("`--sha256` pins the digest of a single archive. Use `--lockfile` to pin the archives of several versions.")]
+  Sha256ForSeveralVersions,
 }
 
 #[cfg(test)]
 mod tests {
   use super::*;
+  use crate::system_info::{Platform, PlatformArch, PlatformOS};
   use pretty_assertions::assert_eq;
   use std::str::FromStr;
 
@@ -275,43 +947,47 @@ mod tests {
     let test_cases = vec![
       (
         Install {
-          version: Some(UserVersion::from_str("4.13.0").unwrap()),
+          versions: vec![UserVersion::from_str("4.13.0").unwrap()],
           nightly: false,
           latest: false,
           force: false,
           progress: ProgressConfig::Never,
+          ..Install::default()
         },
-        Ok(Some(UserVersion::from_str("4.13.0").unwrap())),
+        Ok(vec![UserVersion::from_str("4.13.0").unwrap()]),
       ),
       (
         Install {
-          version: None,
+          versions: vec![],
           nightly: true,
           latest: false,
           force: false,
           progress: ProgressConfig::Never,
+          ..Install::default()
         },
-        Ok(Some(UserVersion::Full(Version::Nightly(
+        Ok(vec![UserVersion::Full(Version::Nightly(
           "nightly".to_string(),
-        )))),
+        ))]),
       ),
       (
         Install {
-          version: None,
+          versions: vec![],
           nightly: false,
           latest: true,
           force: false,
           progress: ProgressConfig::Never,
+          ..Install::default()
         },
-        Ok(Some(UserVersion::Full(Version::Latest))),
+        Ok(vec![UserVersion::Full(Version::Latest)]),
       ),
       (
         Install {
-          version: Some(UserVersion::from_str("4.13.0").unwrap()),
+          versions: vec![UserVersion::from_str("4.13.0").unwrap()],
           nightly: true,
           latest: false,
           force: false,
           progress: ProgressConfig::Never,
+          ..Install::default()
         },
         Err(Error::TooManyVersionsProvided),
       ),
@@ -319,7 +995,7 @@ mod tests {
 
     for (install, expected) in test_cases {
       assert_eq!(
-        format!("{:?}", install.resolve_version()),
+        format!("{:?}", install.resolve_versions()),
         format!("{:?}", expected)
       );
     }
@@ -332,11 +1008,12 @@ mod tests {
     assert!(!config.default_version_dir().exists());
 
     Install {
-      version: UserVersion::from_str("4.13.0").ok(),
+      versions: vec![UserVersion::from_str("4.13.0").unwrap()],
       nightly: false,
       latest: false,
       force: false,
       progress: ProgressConfig::Never,
+      ..Install::default()
     }
     .apply(&config)
     .expect("Can't install");
@@ -358,18 +1035,19 @@ mod tests {
     let config = create_test_config();
 
     Install {
-      version: None,
+      versions: vec![],
       nightly: false,
       latest: true,
       force: false,
       progress: ProgressConfig::Never,
+      ..Install::default()
     }
     .apply(&config)
     .expect("Can't install");
 
-    let latest_release =
-      remote_pact_index::latest(config.repo_urls()).expect("Can't get pact version list");
-    let latest_version = latest_release.tag;
+    let releases =
+      remote_pact_index::list_for_major(&config, None).expect("Can't get pact version list");
+    let latest_version = &remote_pact_index::pick_latest(&releases).unwrap().tag;
     assert!(config.installations_dir().exists());
     assert!(config
       .installations_dir()
@@ -385,19 +1063,23 @@ mod tests {
     let config = create_test_config();
 
     Install {
-      version: None,
+      versions: vec![],
       nightly: true,
       latest: false,
       force: false,
       progress: ProgressConfig::Never,
+      ..Install::default()
     }
     .apply(&config)
     .expect("Can't install");
 
-    let nightly_release =
-      remote_pact_index::get_by_tag(config.repo_urls(), &String::from("nightly"))
-        .expect("Can't get pact version list");
-    let nightly_version = nightly_release.tag;
+    let releases =
+      remote_pact_index::list_for_major(&config, None).expect("Can't get pact version list");
+    let nightly_version = &releases
+      .iter()
+      .find(|release| release.tag.to_string() == "nightly")
+      .expect("Can't find the nightly release")
+      .tag;
     assert!(config.installations_dir().exists());
     assert!(config
       .installations_dir()
@@ -411,29 +1093,446 @@ mod tests {
   fn test_uninstallable_version() {
     let config = create_test_config();
     let result = Install {
-      version: Some(UserVersion::Full(Version::Bypassed)),
+      versions: vec![UserVersion::Full(Version::Bypassed)],
       nightly: false,
       latest: false,
       force: false,
       progress: ProgressConfig::Never,
+      ..Install::default()
     }
     .apply(&config);
 
     assert!(matches!(result, Err(Error::UninstallableVersion { .. })));
   }
 
+  #[test]
+  fn test_not_enough_disk_space() {
+    let config = create_test_config();
+    assert!(Install::check_disk_space(1024, &config).is_ok());
+    let result = Install::check_disk_space(u64::MAX, &config);
+    assert!(matches!(result, Err(Error::NotEnoughDiskSpace { .. })));
+  }
+
+  #[test]
+  fn test_only_glibc_builds_on_musl() {
+    let release = Release {
+      tag: Version::parse("4.13.0").unwrap(),
+      name: None,
+      draft: false,
+      prerelease: false,
+      created_at: None,
+      published_at: None,
+      markdown: None,
+      assets: vec![remote_pact_index::Asset::new(
+        url::Url::parse("https://example.com/pact-4.13.0-linux-22.04.zip").unwrap(),
+      )],
+      source: None,
+    };
+    let requested_version = UserVersion::from_str("4.13.0").unwrap();
+    let target = |arch| AssetTarget::new(Platform(PlatformOS::Linux, arch));
+
+    let musl = target(PlatformArch::X64Musl);
+    assert_eq!(release.asset_for(&musl), None);
+    let error = Install::missing_asset_error(&release, &requested_version, &musl);
+    assert!(matches!(error, Error::OnlyGlibcBuilds { .. }));
+
+    let arm64 = target(PlatformArch::Arm64);
+    let error = Install::missing_asset_error(&release, &requested_version, &arm64);
+    assert!(matches!(error, Error::CantFindReleaseAsset { .. }));
+  }
+
   #[test]
   fn test_too_many_versions() {
     let config = create_test_config();
     let result = Install {
-      version: Some(UserVersion::from_str("4.13.0").unwrap()),
+      versions: vec![UserVersion::from_str("4.13.0").unwrap()],
       nightly: true,
       latest: false,
       force: false,
       progress: ProgressConfig::Never,
+      ..Install::default()
     }
     .apply(&config);
 
     assert!(matches!(result, Err(Error::TooManyVersionsProvided)));
   }
+
+  /// Writes a gzipped tarball holding a `pact` shell script, as Pact archives do.
+  fn write_test_archive(path: &std::path::Path) {
+    let mut builder = tar::Builder::new(flate2::write::GzEncoder::new(
+      std::fs::File::create(path).unwrap(),
+      flate2::Compression::default(),
+    ));
+    let script = b"#!/bin/sh\necho 5.1.0-rc\n";
+    let mut header = tar::Header::new_gnu();
+    header.set_size(script.len() as u64);
+    header.set_mode(0o755);
+    builder
+      .append_data(&mut header, "pact-5.1.0/pact", &script[..])
+      .unwrap();
+    builder.into_inner().unwrap().finish().unwrap();
+  }
+
+  #[test]
+  fn test_install_local_archive() {
+    let config = create_test_config();
+    let archive_dir = tempfile::tempdir().unwrap();
+    let archive_path = archive_dir.path().join("pact-5.1.0-linux-x64.tar.gz");
+    write_test_archive(&archive_path);
+
+    Install {
+      archive: Some(archive_path),
+      label: Some(Version::parse("5.1.0-rc").unwrap()),
+      progress: ProgressConfig::Never,
+      ..Install::default()
+    }
+    .apply(&config)
+    .expect("Can't install the archive");
+
+    let version = Version::parse("5.1.0-rc").unwrap();
+    assert!(version
+      .installation_path(&config)
+      .join("bin")
+      .join("pact")
+      .exists());
+    assert!(config.default_version_dir().exists());
+  }
+
+  #[test]
+  fn test_concurrent_installs_of_a_version() {
+    let config = create_test_config();
+    let archive_dir = tempfile::tempdir().unwrap();
+    let archive_path = archive_dir.path().join("pact-5.1.0-linux-x64.tar.gz");
+    write_test_archive(&archive_path);
+
+    // The installers wait for each other, then find the version installed instead of failing.
+    std::thread::scope(|scope| {
+      for _ in 0..4 {
+        scope.spawn(|| {
+          Install {
+            archive: Some(archive_path.clone()),
+            label: Some(Version::parse("5.1.0-rc").unwrap()),
+            progress: ProgressConfig::Never,
+            ..Install::default()
+          }
+          .apply(&config)
+          .expect("Can't install the archive");
+        });
+      }
+    });
+
+    let version = Version::parse("5.1.0-rc").unwrap();
+    assert!(version
+      .installation_path(&config)
+      .join("bin")
+      .join("pact")
+      .exists());
+    // The installers that waited cleaned up after the first one.
+    let portals: Vec<_> = std::fs::read_dir(config.installations_dir().join(".downloads"))
+      .unwrap()
+      .map(|entry| entry.unwrap().file_name())
+      .collect();
+    assert!(portals.is_empty());
+  }
+
+  #[test]
+  fn test_install_keeps_the_portals_of_other_installations() {
+    let config = create_test_config();
+    let archive_dir = tempfile::tempdir().unwrap();
+    let archive_path = archive_dir.path().join("pact-5.1.0-linux-x64.tar.gz");
+    write_test_archive(&archive_path);
+
+    let installations_dir = config.installations_dir();
+    let downloads_dir = installations_dir.join(".downloads");
+    for name in ["v4.13.0", "v5.0.0", ".tmpAbCd"] {
+      std::fs::create_dir_all(downloads_dir.join(name).join("pact")).unwrap();
+    }
+    // Another process is installing 5.0.0, while the installation of 4.13.0 was interrupted.
+    let _lock = Lock::try_exclusive(&lock::version_lock_path(&installations_dir, "v5.0.0"))
+      .unwrap()
+      .unwrap();
+
+    Install {
+      archive: Some(archive_path),
+      label: Some(Version::parse("5.1.0-rc").unwrap()),
+      progress: ProgressConfig::Never,
+      ..Install::default()
+    }
+    .apply(&config)
+    .expect("Can't install the archive");
+
+    assert!(downloads_dir.join("v5.0.0").join("pact").exists());
+    assert!(!downloads_dir.join("v4.13.0").exists());
+    assert!(!downloads_dir.join(".tmpAbCd").exists());
+  }
+
+  #[test]
+  fn test_force_keeps_the_installation_until_replaced() {
+    let config = create_test_config();
+    let archive_dir = tempfile::tempdir().unwrap();
+    let archive_path = archive_dir.path().join("pact-5.1.0-linux-x64.tar.gz");
+    write_test_archive(&archive_path);
+    let version = Version::parse("5.1.0-rc").unwrap();
+    let install = |archive: &std::path::Path| {
+      Install {
+        archive: Some(archive.to_path_buf()),
+        label: Some(version.clone()),
+        force: true,
+        progress: ProgressConfig::Never,
+        ..Install::default()
+      }
+      .apply(&config)
+    };
+    install(&archive_path).expect("Can't install the archive");
+    let marker = version.installation_path(&config).join("marker");
+    std::fs::write(&marker, "").unwrap();
+
+    let corrupted_path = archive_dir.path().join("corrupted.tar.gz");
+    std::fs::write(&corrupted_path, b"\x1f\x8b\x08\x00corrupted").unwrap();
+    let result = install(&corrupted_path);
+    assert!(matches!(
+      result,
+      Err(Error::CantInstallArchive {
+        source: DownloaderError::CantExtractFile { .. }
+      })
+    ));
+    assert!(marker.exists());
+    assert!(config
+      .default_version_dir()
+      .join("bin")
+      .join("pact")
+      .exists());
+
+    install(&archive_path).expect("Can't reinstall the archive");
+    assert!(!marker.exists());
+    assert!(config
+      .default_version_dir()
+      .join("bin")
+      .join("pact")
+      .exists());
+    let leftovers = config
+      .installations_dir()
+      .join(".downloads")
+      .join(version.v_str());
+    assert_eq!(leftovers.read_dir().unwrap().count(), 0);
+  }
+
+  #[test]
+  fn test_install_archive_checks_its_checksum() {
+    let config = create_test_config();
+    let archive_dir = tempfile::tempdir().unwrap();
+    let archive_path = archive_dir.path().join("pact-5.1.0-linux-x64.tar.gz");
+    write_test_archive(&archive_path);
+    let digest = checksum::HashingReader::new(std::fs::File::open(&archive_path).unwrap())
+      .finish()
+      .unwrap();
+    let version = Version::parse("5.1.0-rc").unwrap();
+
+    let result = Install {
+      archive: Some(archive_path.clone()),
+      label: Some(version.clone()),
+      sha256: Some("0".repeat(64).parse().unwrap()),
+      ..Install::default()
+    }
+    .apply(&config);
+    assert!(matches!(
+      result,
+      Err(Error::CantInstallArchive {
+        source: DownloaderError::ChecksumMismatch { .. }
+      })
+    ));
+    assert!(!version.installation_path(&config).exists());
+
+    let lockfile = archive_dir.path().join("pact.lock");
+    std::fs::write(
+      &lockfile,
+      format!("{digest}  pact-5.1.0-linux-x64.tar.gz\n"),
+    )
+    .unwrap();
+    Install {
+      archive: Some(archive_path),
+      label: Some(version.clone()),
+      lockfile: Some(lockfile),
+      ..Install::default()
+    }
+    .apply(&config)
+    .expect("Can't install the archive");
+    assert_eq!(
+      installed_versions::read_manifest(&version.installation_path(&config)).sha256,
+      Some(digest)
+    );
+  }
+
+  #[test]
+  #[cfg(unix)]
+  fn test_install_signed_release() {
+    let mut config = create_test_config();
+    config.arch = PlatformArch::X64;
+    let release_dir = tempfile::tempdir().unwrap();
+    let file_name = format!(
+      "pact-5.1.0-{}-x64.tar.gz",
+      crate::system_info::get_platform().0
+    );
+    let archive_path = release_dir.path().join(&file_name);
+    write_test_archive(&archive_path);
+    let signature_path = release_dir
+      .path()
+      .join(signature::file_name_for(&file_name));
+    let (public_key, minisig) =
+      crate::signature::tests::sign(&std::fs::read(&archive_path).unwrap());
+    let (_, other_minisig) = crate::signature::tests::sign(&std::fs::read(&archive_path).unwrap());
+
+    let asset =
+      |path: &std::path::Path| remote_pact_index::Asset::new(Url::from_file_path(path).unwrap());
+    let source =
+      crate::sources::Source::new("signed", "acme/pact", vec![]).with_public_keys(vec![public_key]);
+    let mut release = Release {
+      tag: Version::parse("5.1.0").unwrap(),
+      name: None,
+      draft: false,
+      prerelease: false,
+      created_at: None,
+      published_at: None,
+      markdown: None,
+      assets: vec![asset(&archive_path)],
+      source: Some(std::sync::Arc::new(source)),
+    };
+    let requested_version = UserVersion::from_str("5.1.0").unwrap();
+    let install = |allow_unsigned, release: &Release| {
+      Install {
+        progress: ProgressConfig::Never,
+        allow_unsigned,
+        force: true,
+        ..Install::default()
+      }
+      .handle_installation(release, &requested_version, &config)
+    };
+    let installed = || release.tag.installation_path(&config).exists();
+
+    assert!(matches!(
+      install(false, &release),
+      Err(Error::Unsigned { .. })
+    ));
+    assert!(!installed());
+
+    release.assets.push(asset(&signature_path));
+    std::fs::write(&signature_path, other_minisig).unwrap();
+    assert!(matches!(
+      install(false, &release),
+      Err(Error::DownloadError {
+        source: DownloaderError::BadSignature { .. }
+      })
+    ));
+    assert!(!installed());
+
+    install(true, &release).expect("Can't install without checking the signature");
+    assert!(installed());
+
+    std::fs::write(&signature_path, minisig).unwrap();
+    install(false, &release).expect("Can't install the signed release");
+    assert!(installed());
+  }
+
+  #[test]
+  fn test_install_several_versions() {
+    let cache_dir = tempfile::tempdir().unwrap();
+    let mut config = create_test_config().with_cache_dir(Some(cache_dir.path().to_path_buf()));
+    config.arch = PlatformArch::X64;
+    let release_dir = tempfile::tempdir().unwrap();
+    let platform = crate::system_info::get_platform().0;
+    let mut releases = vec![];
+    for version in ["4.13.0", "5.0.0", "5.1.0"] {
+      let file_name = format!("pact-{version}-{platform}-x64.tar.gz");
+      write_test_archive(&release_dir.path().join(&file_name));
+      releases.push(format!(
+        r#"{{ "tag": "v{version}", "assets": [{{ "url": "{file_name}" }}] }}"#
+      ));
+    }
+    let index_path = release_dir.path().join("index.json");
+    std::fs::write(
+      &index_path,
+      format!(r#"{{ "releases": [{}] }}"#, releases.join(", ")),
+    )
+    .unwrap();
+    config.index_url = Some(Url::from_file_path(&index_path).unwrap());
+
+    let requested_versions = ["4.13", "5.1", "5", "6"];
+    let result = Install {
+      versions: requested_versions
+        .iter()
+        .map(|version| UserVersion::from_str(version).unwrap())
+        .collect(),
+      progress: ProgressConfig::Never,
+      jobs: 2,
+      ..Install::default()
+    }
+    .apply(&config);
+
+    assert!(matches!(
+      result,
+      Err(Error::SomeInstallationsFailed {
+        failed: 1,
+        total: 4
+      })
+    ));
+    let mut installed = installed_versions::list(config.installations_dir()).unwrap();
+    installed.sort();
+    assert_eq!(
+      installed,
+      vec![
+        Version::parse("4.13.0").unwrap(),
+        Version::parse("5.1.0").unwrap()
+      ]
+    );
+    let manifest = installed_versions::read_manifest(&config.installations_dir().join("v5.1.0"));
+    assert_eq!(
+      manifest.asset_url,
+      Url::from_file_path(
+        release_dir
+          .path()
+          .join(format!("pact-5.1.0-{platform}-x64.tar.gz"))
+      )
+      .ok()
+    );
+    assert_eq!(manifest.arch.as_deref(), Some("x64"));
+    assert_eq!(
+      manifest.pactup_version.as_deref(),
+      Some(env!("CARGO_PKG_VERSION"))
+    );
+    assert!(manifest.sha256.is_some() && manifest.installed_at.is_some());
+    // The default version is the first one requested, whichever is installed first.
+    assert_eq!(
+      config.default_version_dir().canonicalize().unwrap(),
+      config
+        .installations_dir()
+        .join("v4.13.0")
+        .canonicalize()
+        .unwrap()
+    );
+  }
+
+  #[test]
+  fn test_sha256_needs_a_single_version() {
+    let result = Install {
+      versions: vec![
+        UserVersion::from_str("4.13").unwrap(),
+        UserVersion::from_str("5.0").unwrap(),
+      ],
+      sha256: Some(Sha256::of(b"pact")),
+      ..Install::default()
+    }
+    .apply(&create_test_config());
+    assert!(matches!(result, Err(Error::Sha256ForSeveralVersions)));
+  }
+
+  #[test]
+  fn test_install_archive_needs_an_installable_version() {
+    let result = Install {
+      archive: Some(PathBuf::from("pact.tar.gz")),
+      label: Some(Version::parse("my-build").unwrap()),
+      ..Install::default()
+    }
+    .apply(&create_test_config());
+    assert!(matches!(result, Err(Error::InvalidArchiveVersion { .. })));
+  }
 }
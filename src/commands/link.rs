@@ -0,0 +1,153 @@
+use super::command::Command;
+use crate::config::PactupConfig;
+use crate::installed_versions::{self, Manifest};
+use crate::lock;
+use crate::outln;
+use crate::version::Version;
+use colored::Colorize;
+use log::debug;
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+
+#[derive(clap::Parser, Debug)]
+pub struct Link {
+  /// The version to register the build as, e.g. `5.2.0-local`.
+  name: Version,
+
+  /// The `pact` binary, or a directory holding it directly or in `bin/`, such as a nix `result` link.
+  path: PathBuf,
+}
+
+impl Command for Link {
+  type Error = Error;
+
+  fn apply(self, config: &PactupConfig) -> Result<(), Self::Error> {
+    if !self.name.is_installable() {
+      return Err(Error::InvalidName { name: self.name });
+    }
+    let binary = find_binary(&self.path)?;
+    // The same locks as an installation, so that no other process installs or removes the version meanwhile.
+    let _store =
+      lock::lock_store(lock::Mode::Shared, config).map_err(|source| Error::CantLock { source })?;
+    let _version =
+      lock::lock_version(&self.name, config).map_err(|source| Error::CantLock { source })?;
+    let installation_dir = config.installations_dir().join(self.name.v_str());
+    if installation_dir.exists() {
+      return Err(Error::AlreadyInstalled { version: self.name });
+    }
+
+    debug!("Linking {:?} to {:?}", installation_dir, binary);
+    let bin_dir = installation_dir.join("bin");
+    let result = std::fs::create_dir_all(&bin_dir)
+      .and_then(|()| {
+        crate::fs::symlink_file(
+          &binary,
+          bin_dir.join(format!("pact{}", std::env::consts::EXE_SUFFIX)),
+        )
+      })
+      .and_then(|()| {
+        let manifest = Manifest {
+          link: Some(binary.clone()),
+          ..Manifest::default()
+        };
+        installed_versions::write_manifest(&installation_dir, &manifest.stamped(&binary))
+      });
+    if let Err(source) = result {
+      std::fs::remove_dir_all(&installation_dir).ok();
+      return Err(Error::CantLink { source });
+    }
+
+    outln!(
+      config,
+      Info,
+      "Linked {} to {}",
+      format!("Pact {}", self.name).cyan(),
+      binary.display()
+    );
+    Ok(())
+  }
+}
+
+/// The absolute path of the `pact` binary at, or in, `path`.
+fn find_binary(path: &Path) -> Result<PathBuf, Error> {
+  let file_name = format!("pact{}", std::env::consts::EXE_SUFFIX);
+  let candidates = [
+    path.to_path_buf(),
+    path.join("bin").join(&file_name),
+    path.join(&file_name),
+  ];
+  candidates
+    .into_iter()
+    .find(|candidate| candidate.is_file())
+    .and_then(|binary| std::path::absolute(binary).ok())
+    .ok_or_else(|| Error::BinaryNotFound {
+      path: path.to_path_buf(),
+    })
+}
+
+#[derive(Debug, Error)]
+pub enum Error {
+  #[error("Can't link a build as {}: use a version number such as `5.2.0-local`, or a nightly name.", name.v_str())]
+  InvalidName { name: Version },
+  #[error("Can't find a Pact binary at {}, nor in its `bin` directory", path.display())]
+  BinaryNotFound { path: PathBuf },
+  #[error("Pact {} is already installed. Uninstall it first to link another build.", version.v_str())]
+  AlreadyInstalled { version: Version },
+  #[error("Can't link the build: {source}")]
+  CantLink { source: std::io::Error },
+  #[error("Can't lock the installations directory: {source}")]
+  CantLock { source: std::io::Error },
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use pretty_assertions::assert_eq;
+
+  #[test]
+  fn test_find_binary() {
+    let build = tempfile::tempdir().unwrap();
+    let file_name = format!("pact{}", std::env::consts::EXE_SUFFIX);
+    std::fs::create_dir_all(build.path().join("bin")).unwrap();
+    std::fs::write(build.path().join("bin").join(&file_name), "").unwrap();
+
+    let binary = build.path().join("bin").join(&file_name);
+    assert_eq!(find_binary(build.path()).unwrap(), binary);
+    assert_eq!(find_binary(&binary).unwrap(), binary);
+    assert!(matches!(
+      find_binary(&build.path().join("missing")),
+      Err(Error::BinaryNotFound { .. })
+    ));
+  }
+
+  #[test]
+  #[cfg(unix)]
+  fn test_link_and_list() {
+    let base_dir = tempfile::tempdir().unwrap();
+    let config = PactupConfig::default().with_base_dir(Some(base_dir.path().to_path_buf()));
+    let build = tempfile::tempdir().unwrap();
+    std::fs::write(build.path().join("pact"), "#!/bin/sh\n").unwrap();
+
+    let version = Version::parse("5.2.0-local").unwrap();
+    Link {
+      name: version.clone(),
+      path: build.path().to_path_buf(),
+    }
+    .apply(&config)
+    .unwrap();
+
+    let installation_dir = version.installation_path(&config);
+    assert_eq!(
+      installed_versions::list(config.installations_dir()).unwrap(),
+      vec![version.clone()]
+    );
+    assert_eq!(
+      installed_versions::link_target(&installation_dir),
+      Some(build.path().join("pact"))
+    );
+    assert_eq!(
+      std::fs::read_link(installation_dir.join("bin").join("pact")).unwrap(),
+      build.path().join("pact")
+    );
+  }
+}
@@ -1,28 +1,37 @@
 use crate::alias::{list_aliases, StoredAlias};
 use crate::config::PactupConfig;
 use crate::current_version::current_version;
+use crate::installed_versions::{Installation, Manifest};
 use crate::version::Version;
 use colored::Colorize;
+use indicatif::HumanBytes;
 use std::collections::HashMap;
 use thiserror::Error;
 
 #[derive(clap::Parser, Debug)]
-pub struct LsLocal {}
+pub struct LsLocal {
+  /// Show where each version was installed from, its digest, its size and when it was installed.
+  #[arg(long, short)]
+  long: bool,
+}
 
 impl super::command::Command for LsLocal {
   type Error = Error;
 
   fn apply(self, config: &PactupConfig) -> Result<(), Self::Error> {
     let base_dir = config.installations_dir();
-    let mut versions = crate::installed_versions::list(base_dir)
+    let mut installations = crate::installed_versions::list_installations(base_dir)
       .map_err(|source| Error::CantListLocallyInstalledVersion { source })?;
-    versions.insert(0, Version::Bypassed);
-    versions.sort();
+    installations.push(Installation {
+      version: Version::Bypassed,
+      manifest: Manifest::default(),
+    });
+    installations.sort_by(|a, b| a.version.cmp(&b.version));
     let aliases_hash =
       generate_aliases_hash(config).map_err(|source| Error::CantReadAliases { source })?;
     let curr_version = current_version(config).ok().flatten();
 
-    for version in versions {
+    for Installation { version, manifest } in installations {
       let version_aliases = match aliases_hash.get(&version.v_str()) {
         None => String::new(),
         Some(versions) => {
@@ -35,18 +44,77 @@ impl super::command::Command for LsLocal {
         }
       };
 
-      let version_str = format!("* {version}{version_aliases}");
+      let source = manifest
+        .link
+        .as_ref()
+        .map(|target| format!("external, linked to {}", target.display()))
+        .or_else(|| manifest.source.clone())
+        .map(|source| format!(" {}", format!("[{source}]").dimmed()))
+        .unwrap_or_default();
+
+      let version_str = format!("* {version}{version_aliases}{source}");
 
-      if curr_version == Some(version) {
+      if curr_version.as_ref() == Some(&version) {
         println!("{}", version_str.cyan());
       } else {
         println!("{version_str}");
       }
+      if self.long && version != Version::Bypassed {
+        for line in describe_manifest(&manifest) {
+          println!("    {}", line.dimmed());
+        }
+      }
     }
     Ok(())
   }
 }
 
+/// One line per fact recorded about an installation, for `--long`.
+fn describe_manifest(manifest: &Manifest) -> Vec<String> {
+  let mut lines = vec![];
+  if let Some(link) = &manifest.link {
+    lines.push(format!("linked to: {}", link.display()));
+  }
+  if let Some(source) = &manifest.source {
+    lines.push(format!("source:    {source}"));
+  }
+  if let Some(asset_url) = &manifest.asset_url {
+    let variant = manifest
+      .variant
+      .as_ref()
+      .map(|variant| format!(" ({variant})"))
+      .unwrap_or_default();
+    lines.push(format!("asset:     {asset_url}{variant}"));
+  }
+  if let Some(sha256) = &manifest.sha256 {
+    lines.push(format!("sha256:    {sha256}"));
+  }
+  if let Some(size) = manifest.size {
+    lines.push(format!("size:      {}", HumanBytes(size)));
+  }
+  let installed = [
+    manifest
+      .installed_at
+      .map(|installed_at| installed_at.format("%Y-%m-%d %H:%M UTC").to_string()),
+    manifest.arch.as_ref().map(|arch| format!("for {arch}")),
+    manifest
+      .pactup_version
+      .as_ref()
+      .map(|pactup_version| format!("by pactup {pactup_version}")),
+  ];
+  let installed: Vec<String> = installed.into_iter().flatten().collect();
+  if !installed.is_empty() {
+    lines.push(format!("installed: {}", installed.join(", ")));
+  }
+  if let Some(pact_version) = &manifest.pact_version {
+    lines.push(format!("pact:      {pact_version}"));
+  }
+  if lines.is_empty() {
+    lines.push("installed by an older pactup, which didn't record how".to_string());
+  }
+  lines
+}
+
 fn generate_aliases_hash(
   config: &PactupConfig,
 ) -> std::io::Result<HashMap<String, Vec<StoredAlias>>> {
@@ -1,7 +1,8 @@
 use crate::config::PactupConfig;
-use crate::remote_pact_index::{self, Release};
+use crate::remote_pact_index::{self, AssetTarget, Release};
 use crate::user_version::UserVersion;
 use colored::Colorize;
+use indicatif::HumanBytes;
 use thiserror::Error;
 
 #[derive(clap::Parser, Debug)]
@@ -17,6 +18,14 @@ pub struct LsRemote {
   /// Only show the latest matching version.
   #[arg(long)]
   latest: bool,
+
+  /// Show the release date and the download size of each version.
+  #[arg(long, short)]
+  long: bool,
+
+  /// Explain which asset of a version would be installed on this platform, and why the others wouldn't.
+  #[arg(long, value_name = "VERSION", conflicts_with_all = ["filter", "latest"])]
+  assets: Option<UserVersion>,
 }
 
 #[derive(clap::ValueEnum, Clone, Debug, PartialEq)]
@@ -33,11 +42,12 @@ pub enum SortingMethod {
 #[derive(Debug)]
 struct VersionInfo {
   version: String,
+  details: Vec<String>,
   annotations: Vec<String>,
 }
 
 impl VersionInfo {
-  fn new(release: &Release) -> Self {
+  fn new(release: &Release, target: &AssetTarget) -> Self {
     let mut annotations = Vec::new();
 
     if release.draft {
@@ -49,22 +59,40 @@ impl VersionInfo {
     if release.tag.is_nightly() {
       annotations.push("(nightly)".cyan().to_string());
     }
-    if !release.has_supported_asset() {
+    if !release.has_supported_asset(target) {
       annotations.push("(can't install)".red().to_string());
     }
 
     Self {
       version: release.tag.to_string(),
+      details: vec![],
       annotations,
     }
   }
 
+  /// Adds the release date and the size of the asset for the current platform, as aligned columns.
+  fn with_details(mut self, release: &Release, target: &AssetTarget) -> Self {
+    let date = release.published_at.or(release.created_at).map_or_else(
+      || "-".to_string(),
+      |date| date.format("%Y-%m-%d").to_string(),
+    );
+    let size = release
+      .asset_for(target)
+      .and_then(|asset| asset.size)
+      .map_or_else(|| "-".to_string(), |size| HumanBytes(size).to_string());
+
+    self.version = format!("{:<12}", self.version);
+    self.details = vec![format!("{date:<10}"), format!("{size:>10}")];
+    self
+  }
+
   fn display(&self) -> String {
-    if self.annotations.is_empty() {
-      self.version.clone()
-    } else {
-      format!("{} {}", self.version, self.annotations.join(" "))
-    }
+    let columns: Vec<&str> = std::iter::once(&self.version)
+      .chain(&self.details)
+      .chain(&self.annotations)
+      .map(String::as_str)
+      .collect();
+    columns.join(" ").trim_end().to_string()
   }
 }
 
@@ -72,6 +100,10 @@ impl super::command::Command for LsRemote {
   type Error = Error;
 
   fn apply(self, config: &PactupConfig) -> Result<(), Self::Error> {
+    if let Some(version) = &self.assets {
+      return Self::explain_assets(version, config);
+    }
+
     let mut versions = self.fetch_and_filter_versions(config)?;
 
     if versions.is_empty() {
@@ -87,14 +119,15 @@ impl super::command::Command for LsRemote {
       return Ok(());
     }
 
-    Self::print_versions(&versions);
+    self.print_versions(&versions, &AssetTarget::current(config));
     Ok(())
   }
 }
 
 impl LsRemote {
   fn fetch_and_filter_versions(&self, config: &PactupConfig) -> Result<Vec<Release>, Error> {
-    let mut versions = remote_pact_index::list(config.repo_urls())?;
+    let major = self.filter.as_ref().and_then(UserVersion::major);
+    let mut versions = remote_pact_index::list_for_major(config, major)?;
 
     if let Some(ref filter) = self.filter {
       versions.retain(|v| filter.matches(&v.tag, config));
@@ -114,14 +147,85 @@ impl LsRemote {
     }
   }
 
-  fn print_versions(versions: &[Release]) {
+  fn explain_assets(version: &UserVersion, config: &PactupConfig) -> Result<(), Error> {
+    let releases = remote_pact_index::list_for_major(config, version.major())?;
+    let release = version
+      .to_release(&releases, config)
+      .ok_or_else(|| Error::VersionNotFound {
+        version: version.clone(),
+      })?;
+    let target = AssetTarget::current(config);
+    println!(
+      "Assets of {} for {}:",
+      release.tag,
+      describe_target(&target)
+    );
+    let source = release
+      .source
+      .as_ref()
+      .map(|source| format!(", from {}", source.name))
+      .unwrap_or_default();
+    println!(
+      "  {}",
+      format!("parsed with {}{source}", release.naming()).dimmed()
+    );
+    for line in describe_assets(release, &target) {
+      println!("  {line}");
+    }
+    Ok(())
+  }
+
+  fn print_versions(&self, versions: &[Release], target: &AssetTarget) {
     for version in versions {
-      let info = VersionInfo::new(version);
+      let mut info = VersionInfo::new(version, target);
+      if self.long {
+        info = info.with_details(version, target);
+      }
       println!("{}", info.display());
     }
   }
 }
 
+/// The platform, along with the detected distro or the forced Linux variant.
+fn describe_target(target: &AssetTarget) -> String {
+  let mut parts = vec![target.platform.to_string()];
+  if let Some(variant) = target.variant {
+    parts.push(format!("variant {variant}"));
+  }
+  if let Some(distro) = &target.distro {
+    if let Some(id) = &distro.id {
+      let version = distro.version.map(|x| format!(" {x}")).unwrap_or_default();
+      parts.push(format!("{id}{version}"));
+    }
+    if let Some(glibc) = distro.glibc {
+      parts.push(format!("glibc {glibc}"));
+    }
+  }
+  parts.join(", ")
+}
+
+/// One line per asset of `release`, telling whether it is selected for `target`, and why.
+fn describe_assets(release: &Release, target: &AssetTarget) -> Vec<String> {
+  let selected = release.asset_for(target);
+  release
+    .assess_assets(target)
+    .into_iter()
+    .map(|(asset, verdict)| {
+      let file_name = asset.file_name().unwrap_or(asset.download_url.as_str());
+      match verdict {
+        Ok(name) if selected == Some(asset) => {
+          format!("{} {file_name} ({name})", "selected:".green())
+        }
+        Ok(name) => format!(
+          "{} {file_name} ({name}), a better match is available",
+          "skipped:".yellow()
+        ),
+        Err(rejection) => format!("{} {file_name}: {rejection}", "rejected:".red()),
+      }
+    })
+    .collect()
+}
+
 #[derive(Debug, Error)]
 pub enum Error {
   #[error(transparent)]
@@ -131,12 +235,17 @@ pub enum Error {
   },
   #[error("No versions are available.")]
   NoVersionsAvailable,
+  #[error("Can't find a Pact version that matches {version} in remote")]
+  VersionNotFound { version: UserVersion },
 }
 
 #[cfg(test)]
 mod tests {
   use std::str::FromStr;
 
+  use crate::system_info::{
+    get_platform, DistroVersion, LinuxDistro, Platform, PlatformArch, PlatformOS,
+  };
   use crate::version::Version;
 
   use super::*;
@@ -146,16 +255,21 @@ mod tests {
   fn create_test_release(version: &str, prerelease: bool, draft: bool) -> Release {
     Release {
       tag: Version::parse(version).unwrap(),
-      assets: vec![],
-      prerelease,
+      name: None,
       draft,
+      prerelease,
+      created_at: None,
+      published_at: None,
+      markdown: None,
+      assets: vec![],
+      source: None,
     }
   }
 
   fn create_test_asset(name: &str) -> remote_pact_index::Asset {
-    remote_pact_index::Asset {
-      download_url: Url::parse(&format!("https://example.com/download/{name}")).unwrap(),
-    }
+    remote_pact_index::Asset::new(
+      Url::parse(&format!("https://example.com/download/{name}")).unwrap(),
+    )
   }
 
   #[test]
@@ -163,7 +277,7 @@ mod tests {
     let mut release = create_test_release("4.13.0", true, true);
     release.assets = vec![create_test_asset("pact-4.13.0-linux-x64.tar.gz")];
 
-    let info = VersionInfo::new(&release);
+    let info = VersionInfo::new(&release, &AssetTarget::new(get_platform()));
     assert!(info.display().contains("(draft)"));
     assert!(info.display().contains("(prerelease)"));
   }
@@ -174,6 +288,8 @@ mod tests {
       filter: None,
       sort: SortingMethod::Ascending,
       latest: false,
+      long: false,
+      assets: None,
     };
 
     let mut versions = vec![
@@ -195,6 +311,8 @@ mod tests {
       filter: None,
       sort: SortingMethod::Descending,
       latest: false,
+      long: false,
+      assets: None,
     };
 
     let mut versions = vec![
@@ -216,6 +334,8 @@ mod tests {
       filter: Some(UserVersion::from_str("4.13.0").unwrap()),
       sort: SortingMethod::Ascending,
       latest: false,
+      long: false,
+      assets: None,
     };
 
     let config = PactupConfig::default();
@@ -225,6 +345,91 @@ mod tests {
     assert_eq!(filtered[0].tag.to_string(), "v4.13.0");
   }
 
+  #[test]
+  fn test_version_info_with_details() {
+    let mut release = create_test_release("4.13.0", false, false);
+    release.published_at = "2024-10-01T08:30:00Z".parse().ok();
+    let mut asset = create_test_asset(match get_platform() {
+      Platform(PlatformOS::MacOS, PlatformArch::Arm64) => "pact-4.13.0-darwin-aarch64.tar.gz",
+      Platform(PlatformOS::MacOS, _) => "pact-4.13.0-darwin-x64.tar.gz",
+      Platform(PlatformOS::Windows, _) => "pact-4.13.0-windows-x64.zip",
+      _ => "pact-4.13.0-linux-x64.tar.gz",
+    });
+    asset.size = Some(43_821_112);
+    release.assets = vec![asset];
+
+    let target = AssetTarget::new(get_platform());
+    let info = VersionInfo::new(&release, &target).with_details(&release, &target);
+    let display = info.display();
+    assert!(display.starts_with("v4.13.0 "));
+    assert!(display.contains("2024-10-01"));
+    assert!(display.contains("41.79 MiB"));
+  }
+
+  #[test]
+  fn test_version_info_with_missing_details() {
+    let release = create_test_release("4.13.0", false, false);
+    let target = AssetTarget::new(get_platform());
+    let info = VersionInfo::new(&release, &target).with_details(&release, &target);
+    assert!(info.display().contains(" - "));
+  }
+
+  /// Removes the color codes out of `text`, whether or not colors are enabled.
+  fn strip_colors(text: &str) -> String {
+    let mut plain = String::new();
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+      if c == '\x1b' {
+        chars.by_ref().find(|&c| c == 'm');
+      } else {
+        plain.push(c);
+      }
+    }
+    plain
+  }
+
+  #[test]
+  fn test_describe_assets() {
+    let mut release = create_test_release("4.13.0", false, false);
+    release.assets = vec![
+      create_test_asset("pact-4.13.0-linux-20.04.zip"),
+      create_test_asset("pact-4.13.0-linux-aarch64.tar.gz"),
+      create_test_asset("pact-4.13.0-darwin-aarch64.tar.gz"),
+      create_test_asset("pact-4.13.0.sha256"),
+    ];
+    let target = AssetTarget::new(Platform(PlatformOS::Linux, PlatformArch::Arm64));
+    let lines: Vec<String> = describe_assets(&release, &target)
+      .iter()
+      .map(|line| strip_colors(line))
+      .collect();
+    assert_eq!(
+      lines,
+      vec![
+        "rejected: pact-4.13.0-linux-20.04.zip: no architecture in the name, assumed to be x64",
+        "selected: pact-4.13.0-linux-aarch64.tar.gz (4.13.0 linux, arm64, tar.gz)",
+        "rejected: pact-4.13.0-darwin-aarch64.tar.gz: built for another OS (macos)",
+        "rejected: pact-4.13.0.sha256: not a recognized Pact asset name",
+      ]
+    );
+  }
+
+  #[test]
+  fn test_describe_target() {
+    let target = AssetTarget {
+      platform: Platform(PlatformOS::Linux, PlatformArch::X64),
+      distro: Some(LinuxDistro {
+        id: Some("ubuntu".to_string()),
+        version: Some(DistroVersion::new(22, 4)),
+        glibc: Some(DistroVersion::new(2, 35)),
+      }),
+      variant: None,
+    };
+    assert_eq!(
+      describe_target(&target),
+      "linux-x64, ubuntu 22.04, glibc 2.35"
+    );
+  }
+
   #[test]
   fn test_latest_version() {
     let versions = vec![
@@ -253,7 +458,7 @@ mod tests {
       create_test_asset("pact-4.13.0-windows-x64.tar.gz"),
       create_test_asset("pact-4.13.0-darwin-aarch64.tar.gz"),
     ];
-    let info = VersionInfo::new(&release);
+    let info = VersionInfo::new(&release, &AssetTarget::new(get_platform()));
     assert_eq!(info.display(), "v4.13.0");
   }
 
@@ -266,7 +471,7 @@ mod tests {
       create_test_asset("pact-4.13.0-windows-x64.tar.gz"),
       create_test_asset("pact-4.13.0-darwin-aarch64.tar.gz"),
     ];
-    let info = VersionInfo::new(&release);
+    let info = VersionInfo::new(&release, &AssetTarget::new(get_platform()));
     assert!(!info.display().contains("can't install"));
   }
 
@@ -278,7 +483,7 @@ mod tests {
       create_test_asset("pact-5.0.0-darwin-aarch64.tar.gz"),
       create_test_asset("pact-5.0.0-windows-x64.tar.gz"),
     ];
-    let info = VersionInfo::new(&release);
+    let info = VersionInfo::new(&release, &AssetTarget::new(get_platform()));
     assert!(!info.display().contains("can't install"));
   }
 }
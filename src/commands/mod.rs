@@ -1,4 +1,5 @@
 pub mod alias;
+pub mod cache;
 pub mod command;
 pub mod completions;
 pub mod current;
@@ -6,8 +7,10 @@ pub mod default;
 pub mod env;
 pub mod exec;
 pub mod install;
+pub mod link;
 pub mod ls_local;
 pub mod ls_remote;
+pub mod source;
 pub mod unalias;
 pub mod uninstall;
 pub mod r#use;
@@ -0,0 +1,387 @@
+use super::command::Command;
+use crate::config::PactupConfig;
+use crate::outln;
+use crate::remote_pact_index::{AssetNaming, NamingError};
+use crate::signature;
+use crate::sources;
+use colored::Colorize;
+use std::collections::BTreeMap;
+use thiserror::Error;
+
+#[derive(clap::Parser, Debug)]
+pub struct Source {
+  #[clap(subcommand)]
+  action: Action,
+}
+
+#[derive(clap::Subcommand, Debug)]
+enum Action {
+  /// List the sources, in the order they are queried
+  #[clap(name = "list", visible_aliases = &["ls"])]
+  List,
+
+  /// Add a source of Pact releases
+  ///
+  /// Example:
+  /// --------
+  /// pactup source add internal acme/pact --majors 5
+  /// pactup source add fork acme/pact-cli --template 'pact-cli_{version}_{os}_{arch}' --arch-name amd64=x64
+  #[clap(name = "add", verbatim_doc_comment)]
+  Add {
+    /// A short name for the source, recorded with the versions installed from it.
+    name: String,
+
+    /// A GitHub repository (`owner/repo`), or the URL of a static release index.
+    location: String,
+
+    /// Only query this source for these major versions, e.g. `--majors 4,5`.
+    #[clap(long, value_delimiter = ',')]
+    majors: Vec<u64>,
+
+    /// Query this source before the others, so its releases win over theirs.
+    #[clap(long)]
+    first: bool,
+
+    /// How the assets are named, when they don't follow the naming of the Pact releases,
+    /// e.g. `pact-cli_{version}_{os}_{arch}`. The archive extension is left out.
+    /// The placeholders are `{version}`, `{os}`, `{arch}`, `{distro_version}` and `{linkage}`.
+    #[clap(long)]
+    template: Option<String>,
+
+    /// An OS name used by the assets, mapped to one pactup knows, e.g. `--os-name mac=darwin`.
+    #[clap(long = "os-name", value_name = "NAME=KNOWN_NAME", value_parser = parse_synonym)]
+    os_names: Vec<(String, String)>,
+
+    /// An architecture name used by the assets, mapped to one pactup knows, e.g. `--arch-name amd64=x64`.
+    #[clap(long = "arch-name", value_name = "NAME=KNOWN_NAME", value_parser = parse_synonym)]
+    arch_names: Vec<(String, String)>,
+
+    /// A minisign public key the assets of the source are signed with. Can be repeated.
+    /// Installs from the source then require a valid `<asset>.minisig` signature.
+    #[clap(long = "public-key", value_name = "KEY")]
+    public_keys: Vec<String>,
+  },
+
+  /// Pin a minisign public key the assets of a source are signed with, including a built-in source
+  ///
+  /// Installs from the source then require a valid `<asset>.minisig` signature.
+  #[clap(name = "trust")]
+  Trust {
+    name: String,
+    /// The public key, as the second line of a `minisign.pub` file.
+    public_key: String,
+  },
+
+  /// Remove a source
+  #[clap(name = "remove", visible_aliases = &["rm"])]
+  Remove { name: String },
+
+  /// Forget the configured sources and go back to the built-in ones
+  #[clap(name = "reset")]
+  Reset,
+}
+
+impl Command for Source {
+  type Error = Error;
+
+  fn apply(self, config: &PactupConfig) -> Result<(), Self::Error> {
+    match self.action {
+      Action::List => list(config)?,
+      Action::Add {
+        name,
+        location,
+        majors,
+        first,
+        template,
+        os_names,
+        arch_names,
+        public_keys,
+      } => {
+        let naming = AssetNaming {
+          template,
+          os_names: BTreeMap::from_iter(os_names),
+          arch_names: BTreeMap::from_iter(arch_names),
+        };
+        let source = sources::Source::new(name, location, majors)
+          .with_naming(naming)
+          .with_public_keys(public_keys);
+        add(config, source, first)?;
+      }
+      Action::Trust { name, public_key } => trust(config, &name, public_key)?,
+      Action::Remove { name } => remove(config, &name)?,
+      Action::Reset => {
+        sources::reset(config)?;
+        outln!(config, Info, "Using the built-in sources");
+      }
+    }
+    Ok(())
+  }
+}
+
+fn list(config: &PactupConfig) -> Result<(), Error> {
+  if let Some(index_url) = &config.index_url {
+    outln!(
+      config,
+      Error,
+      "{} the release index {} replaces these sources",
+      "warning:".yellow().bold(),
+      index_url
+    );
+  }
+  for source in sources::load(config)? {
+    let majors = if source.majors.is_empty() {
+      "any version".to_string()
+    } else {
+      source
+        .majors
+        .iter()
+        .map(|major| format!("{major}.x"))
+        .collect::<Vec<_>>()
+        .join(", ")
+    };
+    let builtin = if source.is_builtin() { " built-in" } else { "" };
+    println!(
+      "* {} {} {}",
+      source.name.cyan(),
+      source.location,
+      format!("({majors}){builtin}").dimmed()
+    );
+    if !source.naming.is_default() {
+      println!("  {}", format!("assets: {}", source.naming).dimmed());
+    }
+    for public_key in &source.public_keys {
+      println!("  {}", format!("signed with: {public_key}").dimmed());
+    }
+  }
+  Ok(())
+}
+
+fn add(config: &PactupConfig, source: sources::Source, first: bool) -> Result<(), Error> {
+  validate(&source)?;
+  let mut all_sources = sources::load(config)?;
+  if all_sources.iter().any(|x| x.name == source.name) {
+    return Err(Error::AlreadyExists { name: source.name });
+  }
+  outln!(
+    config,
+    Info,
+    "Added the source {} ({})",
+    source.name.cyan(),
+    source.location
+  );
+  if first {
+    all_sources.insert(0, source);
+  } else {
+    all_sources.push(source);
+  }
+  sources::save(config, &all_sources)?;
+  Ok(())
+}
+
+fn parse_synonym(value: &str) -> Result<(String, String), String> {
+  value
+    .split_once('=')
+    .filter(|(name, known)| !name.is_empty() && !known.is_empty())
+    .map(|(name, known)| (name.to_string(), known.to_string()))
+    .ok_or_else(|| format!("expected NAME=KNOWN_NAME, got {value:?}"))
+}
+
+fn validate(source: &sources::Source) -> Result<(), Error> {
+  if sources::is_builtin(&source.name) {
+    return Err(Error::BuiltinName {
+      name: source.name.clone(),
+    });
+  }
+  let valid_name = !source.name.is_empty()
+    && source
+      .name
+      .chars()
+      .all(|c| c.is_ascii_alphanumeric() || "-_.".contains(c));
+  if !valid_name {
+    return Err(Error::InvalidName {
+      name: source.name.clone(),
+    });
+  }
+  let is_repo = matches!(
+    source.location.split('/').collect::<Vec<_>>()[..],
+    [owner, repo] if !owner.is_empty() && !repo.is_empty()
+  );
+  if !is_repo && source.index_url().is_none() {
+    return Err(Error::InvalidLocation {
+      location: source.location.clone(),
+    });
+  }
+  source.naming.validate()?;
+  for public_key in &source.public_keys {
+    validate_public_key(public_key)?;
+  }
+  Ok(())
+}
+
+fn validate_public_key(public_key: &str) -> Result<(), Error> {
+  signature::parse_public_key(public_key).map_err(|source| Error::InvalidPublicKey {
+    key: public_key.to_string(),
+    source,
+  })?;
+  Ok(())
+}
+
+fn trust(config: &PactupConfig, name: &str, public_key: String) -> Result<(), Error> {
+  validate_public_key(&public_key)?;
+  let mut all_sources = sources::load(config)?;
+  let source = all_sources
+    .iter_mut()
+    .find(|x| x.name == name)
+    .ok_or_else(|| Error::NotFound {
+      name: name.to_string(),
+    })?;
+  if !source.public_keys.contains(&public_key) {
+    source.public_keys.push(public_key);
+  }
+  sources::save(config, &all_sources)?;
+  outln!(
+    config,
+    Info,
+    "Pinned the public key for {}, its assets must now be signed",
+    name.cyan()
+  );
+  Ok(())
+}
+
+fn remove(config: &PactupConfig, name: &str) -> Result<(), Error> {
+  let mut all_sources = sources::load(config)?;
+  let count = all_sources.len();
+  all_sources.retain(|x| x.name != name);
+  if all_sources.len() == count {
+    return Err(Error::NotFound {
+      name: name.to_string(),
+    });
+  }
+  sources::save(config, &all_sources)?;
+  outln!(config, Info, "Removed the source {}", name.cyan());
+  Ok(())
+}
+
+#[derive(Debug, Error)]
+pub enum Error {
+  #[error(transparent)]
+  Sources {
+    #[from]
+    source: sources::Error,
+  },
+  #[error("A source named {name} already exists")]
+  AlreadyExists { name: String },
+  #[error("{name} is a built-in source, its repository is set with `--pact-4x-repo` or `--pact-5x-repo`. Use `pactup source reset` to restore it.")]
+  BuiltinName { name: String },
+  #[error("Invalid source name {name:?}: use letters, digits, `-`, `_` and `.`")]
+  InvalidName { name: String },
+  #[error("Invalid source location {location:?}: expected a GitHub repository (`owner/repo`) or a release index URL")]
+  InvalidLocation { location: String },
+  #[error("Can't find a source named {name}")]
+  NotFound { name: String },
+  #[error("Invalid public key {key:?}: {source}")]
+  InvalidPublicKey {
+    key: String,
+    source: minisign_verify::Error,
+  },
+  #[error("Invalid asset naming: {source}")]
+  InvalidNaming {
+    #[from]
+    source: NamingError,
+  },
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use pretty_assertions::assert_eq;
+
+  #[test]
+  fn test_add_and_remove_sources() {
+    let base_dir = tempfile::tempdir().unwrap();
+    let config = PactupConfig::default().with_base_dir(Some(base_dir.path().to_path_buf()));
+
+    let internal = sources::Source::new("internal", "acme/pact", vec![5]);
+    add(&config, internal.clone(), true).unwrap();
+    assert!(matches!(
+      add(&config, internal.clone(), false),
+      Err(Error::AlreadyExists { .. })
+    ));
+    let names = |config| -> Vec<String> {
+      sources::load(config)
+        .unwrap()
+        .into_iter()
+        .map(|x| x.name)
+        .collect()
+    };
+    assert_eq!(names(&config), vec!["internal", "pact-5", "pact-4"]);
+
+    remove(&config, sources::PACT_5).unwrap();
+    assert_eq!(names(&config), vec!["internal", "pact-4"]);
+    assert!(matches!(
+      remove(&config, sources::PACT_5),
+      Err(Error::NotFound { .. })
+    ));
+  }
+
+  #[test]
+  fn test_validate() {
+    let source = |name: &str, location: &str| sources::Source::new(name, location, vec![]);
+    assert!(validate(&source("internal", "acme/pact")).is_ok());
+    assert!(validate(&source("mirror", "file:///srv/pact/index.json")).is_ok());
+    assert!(matches!(
+      validate(&source("pact-4", "acme/pact")),
+      Err(Error::BuiltinName { .. })
+    ));
+    assert!(matches!(
+      validate(&source("my fork", "acme/pact")),
+      Err(Error::InvalidName { .. })
+    ));
+    assert!(matches!(
+      validate(&source("internal", "acme")),
+      Err(Error::InvalidLocation { .. })
+    ));
+
+    let naming = AssetNaming {
+      template: Some("pact-cli_{version}_{arch}".to_string()),
+      ..AssetNaming::default()
+    };
+    assert!(matches!(
+      validate(&source("fork", "acme/pact-cli").with_naming(naming)),
+      Err(Error::InvalidNaming {
+        source: NamingError::MissingPlaceholder
+      })
+    ));
+  }
+
+  #[test]
+  fn test_trust() {
+    let base_dir = tempfile::tempdir().unwrap();
+    let config = PactupConfig::default().with_base_dir(Some(base_dir.path().to_path_buf()));
+    let (public_key, _) = crate::signature::tests::sign(b"pact");
+
+    trust(&config, sources::PACT_5, public_key.clone()).unwrap();
+    trust(&config, sources::PACT_5, public_key.clone()).unwrap();
+    let pact_5 = sources::load(&config).unwrap().remove(0);
+    assert_eq!(pact_5.public_keys, vec![public_key]);
+
+    assert!(matches!(
+      trust(&config, sources::PACT_5, "RWQnotakey".to_string()),
+      Err(Error::InvalidPublicKey { .. })
+    ));
+    assert!(matches!(
+      trust(&config, "missing", pact_5.public_keys[0].clone()),
+      Err(Error::NotFound { .. })
+    ));
+  }
+
+  #[test]
+  fn test_parse_synonym() {
+    assert_eq!(
+      parse_synonym("amd64=x64"),
+      Ok(("amd64".to_string(), "x64".to_string()))
+    );
+    assert!(parse_synonym("amd64").is_err());
+    assert!(parse_synonym("=x64").is_err());
+  }
+}
@@ -2,6 +2,7 @@ use super::command::Command;
 use crate::config::PactupConfig;
 use crate::fs::remove_symlink_dir;
 use crate::installed_versions;
+use crate::lock;
 use crate::outln;
 use crate::user_version::UserVersion;
 use crate::version::Version;
@@ -51,6 +52,7 @@ impl Command for Uninstall {
       .to_version(&all_versions, config)
       .ok_or(Error::CantFindVersion)?;
 
+    let _lock = lock::lock_version(version, config).map_err(|source| Error::CantLock { source })?;
     let matching_aliases = version.find_aliases(config)?;
     let root_path = version
       .root_path(config)
@@ -58,14 +60,27 @@ impl Command for Uninstall {
         version: version.clone(),
       })?;
 
+    // The installation directory of a linked build only holds a symlink to it, which
+    // `remove_dir_all` deletes without following it.
+    let link_target = installed_versions::link_target(&root_path);
     debug!("Removing Pact version from {:?}", root_path);
     std::fs::remove_dir_all(root_path).map_err(|source| Error::CantDeletePactVersion { source })?;
-    outln!(
-      config,
-      Info,
-      "Pact version {} was removed successfully",
-      version.v_str().cyan()
-    );
+    if let Some(link_target) = link_target {
+      outln!(
+        config,
+        Info,
+        "Pact version {} was unlinked, {} was left untouched",
+        version.v_str().cyan(),
+        link_target.display()
+      );
+    } else {
+      outln!(
+        config,
+        Info,
+        "Pact version {} was removed successfully",
+        version.v_str().cyan()
+      );
+    }
 
     for alias in matching_aliases {
       debug!("Removing alias from {:?}", alias.path());
@@ -105,4 +120,6 @@ pub enum Error {
   CantDeletePactVersion { source: std::io::Error },
   #[error("Can't delete symlink: {}", source)]
   CantDeleteSymlink { source: std::io::Error },
+  #[error("Can't lock the installations directory: {}", source)]
+  CantLock { source: std::io::Error },
 }
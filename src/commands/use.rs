@@ -102,7 +102,7 @@ impl Command for Use {
       })?;
     }
 
-    replace_symlink(&version_path, multishell_path)
+    fs::replace_symlink_dir(&version_path, multishell_path)
       .map_err(|source| Error::SymlinkingCreationIssue { source })?;
 
     Ok(())
@@ -129,11 +129,13 @@ fn install_new_version(
   }
 
   Install {
-    version: Some(requested_version.clone()),
+    versions: vec![requested_version.clone()],
     ..Install::default()
   }
   .apply(config)
-  .map_err(|source| Error::InstallError { source })?;
+  .map_err(|source| Error::InstallError {
+    source: Box::new(source),
+  })?;
 
   Use {
     version: Some(UserVersionReader::Direct(requested_version)),
@@ -145,20 +147,6 @@ fn install_new_version(
   Ok(())
 }
 
-/// Tries to delete `from`, and then tries to symlink `from` to `to` anyway.
-/// If the symlinking fails, it will return the errors in the following order:
-/// * The deletion error (if exists)
-/// * The creation error
-///
-/// This way, we can create a symlink if it is missing.
-fn replace_symlink(from: &std::path::Path, to: &std::path::Path) -> std::io::Result<()> {
-  let symlink_deletion_result = fs::remove_symlink_dir(to);
-  match fs::symlink_dir(from, to) {
-    ok @ Ok(()) => ok,
-    err @ Err(_) => symlink_deletion_result.and(err),
-  }
-}
-
 fn should_install_interactively(requested_version: &UserVersion) -> bool {
   use std::io::{IsTerminal, Write};
 
@@ -215,7 +203,9 @@ pub enum Error {
   #[error("Can't create the symlink: {}", source)]
   SymlinkingCreationIssue { source: std::io::Error },
   #[error(transparent)]
-  InstallError { source: <Install as Command>::Error },
+  InstallError {
+    source: Box<<Install as Command>::Error>,
+  },
   #[error("Can't get locally installed versions: {}", source)]
   VersionListingError { source: installed_versions::Error },
   #[error("Requested version {} is not currently installed", version)]
@@ -242,3 +232,66 @@ pub enum InferVersionError {
   #[error("Could not find any version to use. Maybe you don't have a default version set?\nTry running `pactup default <VERSION>` to set one,\nor create a .pact-version file inside your project to declare a Pact version.")]
   Recursive,
 }
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::log_level::LogLevel;
+  use std::sync::atomic::{AtomicBool, Ordering};
+
+  #[test]
+  fn test_concurrent_use_keeps_the_link() {
+    let base_dir = tempfile::tempdir().unwrap();
+    let multishell_path = base_dir.path().join("multishells").join("shell");
+    let config = PactupConfig::default()
+      .with_base_dir(Some(base_dir.path().to_path_buf()))
+      .with_multishell_path(Some(multishell_path.clone()))
+      .with_log_level(LogLevel::Quiet);
+    let versions = ["5.0.0", "5.1.0"].map(|version| Version::parse(version).unwrap());
+    for version in &versions {
+      std::fs::create_dir_all(version.installation_path(&config)).unwrap();
+    }
+    let use_version = |version: &Version| {
+      Use {
+        version: Some(UserVersionReader::Direct(UserVersion::Full(
+          version.clone(),
+        ))),
+        install_if_missing: false,
+        silent_if_unchanged: false,
+      }
+      .apply(&config)
+      .expect("Can't use the version");
+    };
+    use_version(&versions[0]);
+
+    // Shells switching versions while a `pact` process resolves the link must never find it missing.
+    let done = AtomicBool::new(false);
+    std::thread::scope(|scope| {
+      let reader = scope.spawn(|| {
+        let mut reads = 0;
+        while !done.load(Ordering::Relaxed) {
+          let target = fs::shallow_read_symlink(&multishell_path).expect("The link is missing");
+          assert!(versions
+            .iter()
+            .any(|version| target == version.installation_path(&config)));
+          reads += 1;
+        }
+        reads
+      });
+      let writers: Vec<_> = versions
+        .iter()
+        .map(|version| scope.spawn(|| (0..1000).for_each(|_| use_version(version))))
+        .collect();
+      let results: Vec<_> = writers
+        .into_iter()
+        .map(std::thread::ScopedJoinHandle::join)
+        .collect();
+      done.store(true, Ordering::Relaxed);
+      assert!(reader.join().unwrap() > 0);
+      assert!(results.iter().all(Result::is_ok));
+    });
+
+    let entries = std::fs::read_dir(multishell_path.parent().unwrap()).unwrap();
+    assert_eq!(entries.count(), 1, "temporary links were left behind");
+  }
+}
@@ -1,10 +1,15 @@
+use crate::directories::Directories;
+use crate::http;
 use crate::log_level::LogLevel;
 use crate::path_ext::PathExt;
+use crate::release_backend::ReleaseBackend;
+use crate::system_info::{DistroVersion, PlatformArch};
 use crate::version_file_strategy::VersionFileStrategy;
-use crate::{directories::Directories, system_info::PlatformArch};
+use url::Url;
 
 #[derive(clap::Parser, Debug)]
 pub struct PactupConfig {
+  /// The repository of the built-in `pact-4` source.
   /// <https://github.com/kadena-io/pact>
   #[clap(
     long,
@@ -15,7 +20,8 @@ pub struct PactupConfig {
   )]
   pub pact_4x_repo: String,
 
-  /// <https://github.com/kadena-io/pact>
+  /// The repository of the built-in `pact-5` source.
+  /// <https://github.com/kadena-io/pact-5>
   #[clap(
     long,
     env = "PACTUP_PACT5X_REPO",
@@ -25,6 +31,116 @@ pub struct PactupConfig {
   )]
   pub pact_5x_repo: String,
 
+  /// Where to fetch the list of Pact releases from.
+  #[clap(
+    long,
+    env = "PACTUP_RELEASE_BACKEND",
+    default_value_t,
+    global = true,
+    hide_env_values = true
+  )]
+  pub release_backend: ReleaseBackend,
+
+  /// A static release index (`index.json`) to fetch Pact releases from, instead of the configured sources.
+  /// Accepts `https://` and `file://` URLs, or a local path.
+  #[clap(
+    long,
+    env = "PACTUP_INDEX_URL",
+    global = true,
+    hide_env_values = true,
+    value_parser = parse_index_url
+  )]
+  pub index_url: Option<Url>,
+
+  /// The GitHub API root used by the `github` release backend.
+  /// Point it to your GitHub Enterprise instance, e.g. `https://github.example.com/api/v3`.
+  #[clap(
+    long,
+    env = "PACTUP_GITHUB_API_URL",
+    default_value = "https://api.github.com",
+    global = true,
+    hide_env_values = true
+  )]
+  pub github_api_url: Url,
+
+  /// A GitHub token used by the `github` release backend, e.g. to access private forks.
+  /// Falls back to the `GITHUB_TOKEN` environment variable.
+  #[clap(
+    long,
+    env = "PACTUP_GITHUB_TOKEN",
+    global = true,
+    hide_env_values = true
+  )]
+  github_token: Option<String>,
+
+  /// Don't access the network: resolve versions only from the release cache and the installed versions.
+  #[clap(long, env = "PACTUP_OFFLINE", global = true, hide_env_values = true)]
+  pub offline: bool,
+
+  /// How long, in seconds, to wait for a connection to a server. `0` waits as long as it takes.
+  #[clap(
+    long,
+    env = "PACTUP_CONNECT_TIMEOUT",
+    default_value_t = 10,
+    global = true,
+    hide_env_values = true
+  )]
+  connect_timeout: u64,
+
+  /// How long, in seconds, to wait for a server to answer, or to send more of a download.
+  /// `0` waits as long as it takes.
+  #[clap(
+    long,
+    env = "PACTUP_READ_TIMEOUT",
+    default_value_t = 30,
+    global = true,
+    hide_env_values = true
+  )]
+  read_timeout: u64,
+
+  /// How many times a request failing with a connection error, a server error or a rate limit is tried again.
+  #[clap(
+    long,
+    env = "PACTUP_HTTP_RETRIES",
+    default_value_t = 3,
+    global = true,
+    hide_env_values = true
+  )]
+  http_retries: u32,
+
+  /// A proxy for all requests, e.g. `http://proxy.example.com:3128`.
+  /// Defaults to the `HTTPS_PROXY` and `HTTP_PROXY` environment variables. `NO_PROXY` is honored either way.
+  #[clap(long, env = "PACTUP_PROXY", global = true, hide_env_values = true)]
+  proxy: Option<Url>,
+
+  /// A PEM file of certificates to trust besides the system ones, e.g. the root of a corporate proxy.
+  #[clap(long, env = "PACTUP_CA_BUNDLE", global = true, hide_env_values = true)]
+  ca_bundle: Option<std::path::PathBuf>,
+
+  /// Follow redirects from `https://` to plain `http://` URLs, which are refused by default.
+  #[clap(
+    long,
+    env = "PACTUP_ALLOW_HTTP_REDIRECTS",
+    global = true,
+    hide_env_values = true
+  )]
+  allow_http_redirects: bool,
+
+  /// How long, in seconds, the list of remote versions is cached before being revalidated.
+  #[clap(
+    long,
+    env = "PACTUP_INDEX_CACHE_TTL",
+    default_value_t = 3600,
+    global = true,
+    hide_env_values = true
+  )]
+  index_cache_ttl: u64,
+
+  /// The directory pactup caches downloaded data in.
+  /// Defaults to `pactup` in the XDG cache directory.
+  #[clap(long, env = "PACTUP_CACHE_DIR", global = true, hide_env_values = true)]
+  cache_dir: Option<std::path::PathBuf>,
+
   /// The root directory of pact installations.
   #[clap(
     long = "pactup-dir",
@@ -67,6 +183,16 @@ pub struct PactupConfig {
   )]
   pub arch: PlatformArch,
 
+  /// Install the Linux binaries built on this Ubuntu release (e.g. `22.04`), instead of
+  /// picking the newest one compatible with the system's distro and glibc.
+  #[clap(
+    long,
+    env = "PACTUP_LINUX_VARIANT",
+    global = true,
+    hide_env_values = true
+  )]
+  pub linux_variant: Option<DistroVersion>,
+
   /// A strategy for how to resolve the Pact version. Used whenever `pactup use` or `pactup install` is
   /// called without a version, or when `--use-on-cd` is configured on evaluation.
   #[clap(
@@ -102,15 +228,40 @@ pub struct PactupConfig {
   directories: Directories,
 }
 
+fn parse_index_url(value: &str) -> Result<Url, String> {
+  match Url::parse(value) {
+    // A single letter scheme is a Windows drive letter, not a URL.
+    Ok(url) if url.scheme().len() > 1 => Ok(url),
+    _ => {
+      let path = std::path::absolute(value).map_err(|err| err.to_string())?;
+      Url::from_file_path(&path).map_err(|()| format!("Invalid index path: {}", path.display()))
+    }
+  }
+}
+
 impl Default for PactupConfig {
   fn default() -> Self {
     Self {
       pact_4x_repo: "kadena-io/pact".to_string(),
       pact_5x_repo: "kadena-io/pact-5".to_string(),
+      release_backend: ReleaseBackend::default(),
+      index_url: None,
+      github_api_url: Url::parse("https://api.github.com").unwrap(),
+      github_token: None,
+      offline: false,
+      connect_timeout: 10,
+      read_timeout: 30,
+      http_retries: 3,
+      proxy: None,
+      ca_bundle: None,
+      allow_http_redirects: false,
+      index_cache_ttl: 3600,
+      cache_dir: None,
       base_dir: None,
       multishell_path: None,
       log_level: LogLevel::Info,
       arch: PlatformArch::default(),
+      linux_variant: None,
       version_file_strategy: VersionFileStrategy::default(),
       directories: Directories::default(),
       resolve_engines: None,
@@ -119,9 +270,28 @@ impl Default for PactupConfig {
 }
 
 impl PactupConfig {
-  pub fn repo_urls(&self) -> Vec<&str> {
-    vec![&self.pact_5x_repo, &self.pact_4x_repo]
+  pub fn github_token(&self) -> Option<String> {
+    self
+      .github_token
+      .clone()
+      .or_else(|| std::env::var("GITHUB_TOKEN").ok())
+      .filter(|token| !token.is_empty())
   }
+
+  /// How the HTTP client sends requests.
+  pub fn http_settings(&self) -> http::Settings {
+    let seconds = |seconds| Some(std::time::Duration::from_secs(seconds)).filter(|x| !x.is_zero());
+    http::Settings {
+      connect_timeout: seconds(self.connect_timeout),
+      read_timeout: seconds(self.read_timeout),
+      retries: self.http_retries,
+      proxy: self.proxy.clone(),
+      ca_bundle: self.ca_bundle.clone(),
+      allow_http_redirects: self.allow_http_redirects,
+      ..http::Settings::default()
+    }
+  }
+
   pub fn version_file_strategy(&self) -> VersionFileStrategy {
     self.version_file_strategy
   }
@@ -157,6 +327,31 @@ impl PactupConfig {
       .ensure_exists_silently()
   }
 
+  /// The file the sources managed by `pactup source` are stored in.
+  pub fn sources_path(&self) -> std::path::PathBuf {
+    self.base_dir_with_default().join("sources.json")
+  }
+
+  pub fn cache_dir(&self) -> std::path::PathBuf {
+    self
+      .cache_dir
+      .clone()
+      .unwrap_or_else(|| self.directories.cache_dir())
+  }
+
+  pub fn index_cache_dir(&self) -> std::path::PathBuf {
+    self.cache_dir().join("release-index")
+  }
+
+  /// The directory archives are downloaded to before being extracted.
+  pub fn downloads_dir(&self) -> std::path::PathBuf {
+    self.cache_dir().join("downloads")
+  }
+
+  pub fn index_cache_ttl(&self) -> std::time::Duration {
+    std::time::Duration::from_secs(self.index_cache_ttl)
+  }
+
   pub fn multishell_storage(&self) -> std::path::PathBuf {
     self.directories.multishell_storage()
   }
@@ -172,9 +367,33 @@ impl PactupConfig {
       .ensure_exists_silently()
   }
 
+  #[cfg(test)]
+  pub fn with_index_cache_ttl(mut self, index_cache_ttl: u64) -> Self {
+    self.index_cache_ttl = index_cache_ttl;
+    self
+  }
+
+  #[cfg(test)]
+  pub fn with_cache_dir(mut self, cache_dir: Option<std::path::PathBuf>) -> Self {
+    self.cache_dir = cache_dir;
+    self
+  }
+
   #[cfg(test)]
   pub fn with_base_dir(mut self, base_dir: Option<std::path::PathBuf>) -> Self {
     self.base_dir = base_dir;
     self
   }
+
+  #[cfg(test)]
+  pub fn with_multishell_path(mut self, multishell_path: Option<std::path::PathBuf>) -> Self {
+    self.multishell_path = multishell_path;
+    self
+  }
+
+  #[cfg(test)]
+  pub fn with_log_level(mut self, log_level: LogLevel) -> Self {
+    self.log_level = log_level;
+    self
+  }
 }
@@ -68,6 +68,10 @@ impl Directories {
     modern.ensure_exists_silently()
   }
 
+  pub fn cache_dir(&self) -> PathBuf {
+    cache_dir(self.strategy()).join("pactup")
+  }
+
   pub fn multishell_storage(&self) -> PathBuf {
     let basedirs = self.strategy();
     let dir = runtime_dir(basedirs)
@@ -99,13 +99,61 @@ impl<P: AsRef<Path>> DirectoryPortal<P> {
     Self { temp_dir, target }
   }
 
+  /// Moves the `pact` binary into `bin/`, as [`DirectoryPortal::teleport`] does,
+  /// so it can be inspected before teleporting.
+  pub fn ensure_bin_dir(&self) -> std::io::Result<()> {
+    ensure_bin_dir(self.temp_dir.path())
+  }
+
+  /// Moves the files to `target`, replacing what is there, if anything.
+  ///
+  /// The previous files are moved aside next to the portal, and only removed once the new ones
+  /// are in place. If the new ones can't be moved in, the previous ones are put back.
   pub fn teleport(self) -> std::io::Result<P> {
     ensure_bin_dir(self.temp_dir.path())?;
-    std::fs::rename(&self.temp_dir, &self.target)?;
+    let target = self.target.as_ref();
+    let aside = self.temp_dir.path().with_extension("previous");
+    let replacing = target.symlink_metadata().is_ok();
+    if replacing {
+      debug!("Moving {:?} aside to {:?}", target, aside);
+      std::fs::rename(target, &aside)?;
+    }
+    if let Err(err) = std::fs::rename(&self.temp_dir, target) {
+      if replacing {
+        debug!("Restoring {:?}", target);
+        std::fs::rename(&aside, target)?;
+      }
+      return Err(err);
+    }
+    if replacing {
+      // A leftover is removed with the rest of the portals, by the next installation.
+      if let Err(err) = std::fs::remove_dir_all(&aside) {
+        debug!("Can't remove {:?}: {}", aside, err);
+      }
+    }
     Ok(self.target)
   }
 }
 
+/// Puts back into `target` the files a teleport from `parent_dir` moved aside, when it was
+/// interrupted before the new files took their place.
+pub fn restore_moved_aside(parent_dir: &Path, target: &Path) -> std::io::Result<()> {
+  if target.symlink_metadata().is_ok() {
+    return Ok(());
+  }
+  for entry in std::fs::read_dir(parent_dir)? {
+    let path = entry?.path();
+    if path
+      .extension()
+      .is_some_and(|extension| extension == "previous")
+    {
+      debug!("Restoring {:?} from {:?}", target, path);
+      return std::fs::rename(&path, target);
+    }
+  }
+  Ok(())
+}
+
 impl<P: AsRef<Path>> std::ops::Deref for DirectoryPortal<P> {
   type Target = Path;
   fn deref(&self) -> &Self::Target {
@@ -141,4 +189,23 @@ mod tests {
 
     assert_eq!(file_exists, vec!["README.md"]);
   }
+
+  #[test_log::test]
+  fn test_portal_replaces_the_target() {
+    let tempdir = tempdir().expect("Can't generate a temp directory");
+    let target = tempdir.path().join("target");
+    std::fs::create_dir_all(target.join("old")).unwrap();
+    let parent_dir = tempdir.path().join("portals");
+    let portal = DirectoryPortal::new_in(&parent_dir, &target);
+    std::fs::write(portal.join("new"), "").unwrap();
+    portal.teleport().expect("Can't close directory portal");
+
+    let files: Vec<_> = target
+      .read_dir()
+      .unwrap()
+      .map(|x| x.unwrap().file_name().into_string().unwrap())
+      .collect();
+    assert_eq!(files, vec!["new"]);
+    assert_eq!(parent_dir.read_dir().unwrap().count(), 0);
+  }
 }
@@ -0,0 +1,315 @@
+//! Downloaded archives, kept so that installing them again doesn't download them again.
+//!
+//! Each archive is stored in the downloads directory as `<key>-<file name>`, the key being derived
+//! from its URL, next to a `<key>-<file name>.json` file recording the URL and the SHA-256 digest of
+//! the archive. Archives are hashed again before being reused, so a corrupted file is never installed.
+
+use crate::checksum::{HashingReader, Sha256};
+use log::debug;
+use serde::{Deserialize, Serialize};
+use std::ffi::OsString;
+use std::fs::File;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+use url::Url;
+
+const METADATA_SUFFIX: &str = ".json";
+
+/// What is recorded about an archive, next to it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Metadata {
+  url: Url,
+  sha256: Sha256,
+}
+
+/// An archive in the cache.
+#[derive(Debug, Clone)]
+pub struct Entry {
+  pub path: PathBuf,
+  pub url: Url,
+  pub sha256: Sha256,
+  pub size: u64,
+  /// When the archive was last downloaded or installed.
+  pub last_used: SystemTime,
+}
+
+/// Where the archive at `url` is stored.
+pub fn path_for(downloads_dir: &Path, url: &Url) -> PathBuf {
+  // The digest of the URL tells apart the files of the same name from different releases or sources.
+  let digest = Sha256::of(url.as_str().as_bytes()).to_string();
+  let file_name = url
+    .path_segments()
+    .and_then(Iterator::last)
+    .filter(|name| !name.is_empty())
+    .unwrap_or("download");
+  downloads_dir.join(format!("{}-{file_name}", &digest[..16]))
+}
+
+fn metadata_path(path: &Path) -> PathBuf {
+  let mut metadata_path = OsString::from(path.as_os_str());
+  metadata_path.push(METADATA_SUFFIX);
+  PathBuf::from(metadata_path)
+}
+
+fn read_metadata(path: &Path) -> Option<Metadata> {
+  let text = std::fs::read_to_string(metadata_path(path)).ok()?;
+  serde_json::from_str(&text).ok()
+}
+
+fn hash(path: &Path) -> std::io::Result<Sha256> {
+  HashingReader::new(File::open(path)?).finish()
+}
+
+/// Adds the archive just downloaded from `url` at `path` to the cache, and returns its digest.
+pub fn record(path: &Path, url: &Url) -> std::io::Result<Sha256> {
+  let sha256 = hash(path)?;
+  let metadata = Metadata {
+    url: url.clone(),
+    sha256,
+  };
+  let text = serde_json::to_string(&metadata).expect("Can't serialize the cache metadata");
+  std::fs::write(metadata_path(path), text)?;
+  Ok(sha256)
+}
+
+/// Whether the archive at `url` is in the cache, without verifying it.
+pub fn contains(downloads_dir: &Path, url: &Url) -> bool {
+  let path = path_for(downloads_dir, url);
+  path.is_file() && read_metadata(&path).is_some_and(|metadata| &metadata.url == url)
+}
+
+/// Finds the archive downloaded from `url`, or else another archive with the `expected` digest,
+/// e.g. downloaded from a mirror. The archive must still have the digest it was recorded with.
+pub fn lookup(downloads_dir: &Path, url: &Url, expected: Option<&Sha256>) -> Option<PathBuf> {
+  let by_url = Some(path_for(downloads_dir, url))
+    .filter(|path| read_metadata(path).is_some_and(|metadata| &metadata.url == url));
+  let by_digest = || {
+    let expected = expected?;
+    list(downloads_dir)
+      .ok()?
+      .into_iter()
+      .find(|entry| &entry.sha256 == expected)
+      .map(|entry| entry.path)
+  };
+  let path = by_url.or_else(by_digest)?;
+  let metadata = read_metadata(&path)?;
+
+  let valid = hash(&path).is_ok_and(|digest| {
+    digest == metadata.sha256 && expected.is_none_or(|expected| &digest == expected)
+  });
+  if !valid {
+    debug!(
+      "{:?} doesn't match its digest, removing it from the cache",
+      path
+    );
+    remove(&path);
+    return None;
+  }
+  debug!("Using {:?} from the cache", path);
+  // The modification time tells when the archive was last used, for `pactup cache prune`.
+  File::options()
+    .write(true)
+    .open(&path)
+    .and_then(|file| file.set_modified(SystemTime::now()))
+    .ok();
+  Some(path)
+}
+
+/// Removes an archive from the cache.
+pub fn remove(path: &Path) {
+  std::fs::remove_file(path).ok();
+  std::fs::remove_file(metadata_path(path)).ok();
+}
+
+/// Lists the archives in the cache, leaving out partial downloads.
+pub fn list(downloads_dir: &Path) -> std::io::Result<Vec<Entry>> {
+  let dir = match std::fs::read_dir(downloads_dir) {
+    Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(vec![]),
+    dir => dir?,
+  };
+  let mut entries = vec![];
+  for file in dir {
+    let path = file?.path();
+    let is_metadata = path
+      .file_name()
+      .is_some_and(|name| name.to_string_lossy().ends_with(METADATA_SUFFIX));
+    if is_metadata {
+      continue;
+    }
+    let (Some(metadata), Ok(file_metadata)) = (read_metadata(&path), std::fs::metadata(&path))
+    else {
+      continue;
+    };
+    entries.push(Entry {
+      url: metadata.url,
+      sha256: metadata.sha256,
+      size: file_metadata.len(),
+      last_used: file_metadata.modified()?,
+      path,
+    });
+  }
+  entries.sort_by(|a, b| a.path.cmp(&b.path));
+  Ok(entries)
+}
+
+/// The disk space used by the cache, partial downloads included.
+pub fn size(downloads_dir: &Path) -> u64 {
+  walkdir::WalkDir::new(downloads_dir)
+    .into_iter()
+    .filter_map(Result::ok)
+    .filter_map(|entry| entry.metadata().ok())
+    .filter(std::fs::Metadata::is_file)
+    .map(|metadata| metadata.len())
+    .sum()
+}
+
+/// Removes everything in the cache, partial downloads included.
+pub fn clean(downloads_dir: &Path) -> std::io::Result<()> {
+  match std::fs::remove_dir_all(downloads_dir) {
+    Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(()),
+    result => result,
+  }
+}
+
+/// Removes the files not used for `older_than`, partial downloads included, and returns the archives removed.
+pub fn prune(downloads_dir: &Path, older_than: Duration) -> std::io::Result<Vec<Entry>> {
+  let is_old = |time: SystemTime| {
+    SystemTime::now()
+      .duration_since(time)
+      .is_ok_and(|age| age > older_than)
+  };
+  let entries: Vec<Entry> = list(downloads_dir)?
+    .into_iter()
+    .filter(|entry| is_old(entry.last_used))
+    .collect();
+  for entry in &entries {
+    remove(&entry.path);
+  }
+
+  // Partial downloads along with their state, and files left by older versions of pactup.
+  let Ok(dir) = std::fs::read_dir(downloads_dir) else {
+    return Ok(entries);
+  };
+  let files: Vec<PathBuf> = dir.filter_map(|file| Some(file.ok()?.path())).collect();
+  for path in &files {
+    let is_metadata = path.to_string_lossy().ends_with(METADATA_SUFFIX);
+    let modified = std::fs::metadata(path).and_then(|metadata| metadata.modified());
+    if !is_metadata && modified.is_ok_and(is_old) && read_metadata(path).is_none() {
+      remove(path);
+    }
+  }
+  for path in &files {
+    let archive = path
+      .to_string_lossy()
+      .strip_suffix(METADATA_SUFFIX)
+      .map(PathBuf::from);
+    if archive.is_some_and(|archive| !archive.exists()) {
+      std::fs::remove_file(path).ok();
+    }
+  }
+  Ok(entries)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use pretty_assertions::assert_eq;
+
+  fn download(downloads_dir: &Path, url: &Url, content: &str) -> PathBuf {
+    std::fs::create_dir_all(downloads_dir).unwrap();
+    let path = path_for(downloads_dir, url);
+    std::fs::write(&path, content).unwrap();
+    record(&path, url).unwrap();
+    path
+  }
+
+  #[test]
+  fn test_lookup() {
+    let downloads_dir = tempfile::tempdir().unwrap();
+    let url: Url = "https://example.com/pact-5.1.0-linux-x64.tar.gz"
+      .parse()
+      .unwrap();
+    let mirror: Url = "https://mirror.example.com/pact-5.1.0-linux-x64.tar.gz"
+      .parse()
+      .unwrap();
+    assert_eq!(lookup(downloads_dir.path(), &url, None), None);
+
+    let path = download(downloads_dir.path(), &url, "pact");
+    let digest = Sha256::of(b"pact");
+    assert!(contains(downloads_dir.path(), &url));
+    assert_eq!(lookup(downloads_dir.path(), &url, None), Some(path.clone()));
+    assert_eq!(
+      lookup(downloads_dir.path(), &mirror, Some(&digest)),
+      Some(path.clone())
+    );
+    assert_eq!(lookup(downloads_dir.path(), &mirror, None), None);
+
+    // A corrupted archive is removed instead of being reused.
+    std::fs::write(&path, "corrupted").unwrap();
+    assert_eq!(lookup(downloads_dir.path(), &url, None), None);
+    assert!(!contains(downloads_dir.path(), &url));
+    assert!(list(downloads_dir.path()).unwrap().is_empty());
+  }
+
+  #[test]
+  fn test_lookup_checks_the_expected_digest() {
+    let downloads_dir = tempfile::tempdir().unwrap();
+    let url: Url = "https://example.com/pact.tar.gz".parse().unwrap();
+    download(downloads_dir.path(), &url, "pact");
+    assert_eq!(
+      lookup(downloads_dir.path(), &url, Some(&Sha256::of(b"other"))),
+      None
+    );
+  }
+
+  #[test]
+  fn test_list_and_prune() {
+    let downloads_dir = tempfile::tempdir().unwrap();
+    let old_url: Url = "https://example.com/pact-4.0.0.tar.gz".parse().unwrap();
+    let new_url: Url = "https://example.com/pact-5.0.0.tar.gz".parse().unwrap();
+    let old = download(downloads_dir.path(), &old_url, "old");
+    let new = download(downloads_dir.path(), &new_url, "new");
+    let entries = list(downloads_dir.path()).unwrap();
+    assert_eq!(entries.len(), 2);
+    assert_eq!(size(downloads_dir.path()), 6 + size_of_metadata(&entries));
+
+    let partial = downloads_dir
+      .path()
+      .join("0123456789abcdef-pact.tar.gz.part");
+    std::fs::write(&partial, "partial").unwrap();
+    std::fs::write(metadata_path(&partial), "{}").unwrap();
+    let two_days_ago = SystemTime::now() - Duration::from_secs(2 * 24 * 3600);
+    for path in [&old, &partial, &metadata_path(&new)] {
+      File::options()
+        .write(true)
+        .open(path)
+        .unwrap()
+        .set_modified(two_days_ago)
+        .unwrap();
+    }
+
+    let pruned = prune(downloads_dir.path(), Duration::from_secs(24 * 3600)).unwrap();
+    assert_eq!(
+      pruned.iter().map(|x| &x.url).collect::<Vec<_>>(),
+      vec![&old_url]
+    );
+    let entries = list(downloads_dir.path()).unwrap();
+    assert_eq!(
+      entries.iter().map(|x| &x.url).collect::<Vec<_>>(),
+      vec![&new_url]
+    );
+    assert!(!partial.exists());
+    assert!(!metadata_path(&partial).exists());
+
+    clean(downloads_dir.path()).unwrap();
+    assert_eq!(size(downloads_dir.path()), 0);
+  }
+
+  fn size_of_metadata(entries: &[Entry]) -> u64 {
+    entries
+      .iter()
+      .map(|x| std::fs::metadata(metadata_path(&x.path)).unwrap().len())
+      .sum()
+  }
+}
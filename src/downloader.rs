@@ -1,10 +1,17 @@
 use crate::archive::{Archive, Error as ExtractError};
-use crate::directory_portal::DirectoryPortal;
-use crate::progress::ResponseProgress;
+use crate::checksum::{Checksum, HashingReader, Sha256};
+use crate::directory_portal::{restore_moved_aside, DirectoryPortal};
+use crate::download_cache;
+use crate::http::StatusCode;
+use crate::installed_versions::{self, Manifest};
+use crate::lock::{version_lock_path, Lock};
+use crate::signature::{Signature, VerifyingReader};
 use crate::system_info::PlatformArch;
+use crate::transfer::{self, Transfer};
 use crate::version::Version;
-use indicatif::ProgressDrawTarget;
 use log::debug;
+use std::fs::File;
+use std::io::Read;
 use std::path::Path;
 use std::path::PathBuf;
 use thiserror::Error;
@@ -34,76 +41,295 @@ pub enum Error {
     version: Version,
     arch: PlatformArch,
   },
+  #[error("The downloaded Pact binary can't run on this machine: {}", source)]
+  IncompatibleBinary { source: crate::elf::Mismatch },
+  #[error("The downloaded archive doesn't match the checksum from {}: expected {}, got {}", expected.origin, expected.digest, actual)]
+  ChecksumMismatch { expected: Checksum, actual: Sha256 },
+  #[error("Can't verify the signature of the downloaded archive: {}", source)]
+  BadSignature {
+    #[from]
+    source: crate::signature::Error,
+  },
+  #[error("Can't download the archive: {}", source)]
+  Transfer {
+    #[from]
+    source: transfer::Error,
+  },
   #[error("Version already installed at {:?}", path)]
   VersionAlreadyInstalled { path: PathBuf },
 }
 
+/// What a downloaded archive is checked against before it is installed.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Integrity<'a> {
+  pub checksum: Option<&'a Checksum>,
+  pub signature: Option<&'a Signature>,
+}
+
 /// Install a pact asset from a URL into a directory
+///
+/// The archive is downloaded to the cache first, so an interrupted download can be resumed, and
+/// is kept there for later installs. It is hashed and its signature verified as it is extracted.
+/// `manifest` tells where the archive comes from, and is completed with how it was installed.
+#[allow(clippy::too_many_arguments)]
 pub fn install_pact_dist<P: AsRef<Path>>(
   version: &Version,
   download_url: &Url,
   installations_dir: P,
   arch: PlatformArch,
-  show_progress: bool,
+  transfer: &Transfer,
   force: bool,
+  integrity: Integrity<'_>,
+  manifest: Manifest,
 ) -> Result<(), Error> {
-  let version_installation_dir = PathBuf::from(installations_dir.as_ref()).join(version.v_str());
+  let portal = create_portal(version, installations_dir.as_ref(), force)?;
+
+  debug!("Going to call for {}", download_url);
+  let not_found = || Error::VersionNotFound {
+    version: version.clone(),
+    arch,
+  };
+  // Local archives are extracted where they are, the others are kept in the download cache.
+  let (path, cached) = match download_url.to_file_path() {
+    Ok(path) if download_url.scheme() == "file" => (path, false),
+    _ => match transfer.fetch(download_url, integrity.checksum.map(|x| &x.digest)) {
+      Ok(path) => (path, true),
+      Err(transfer::Error::Status {
+        status: StatusCode::NOT_FOUND,
+        ..
+      }) => return Err(not_found()),
+      Err(err) => return Err(err.into()),
+    },
+  };
+  if !path.is_file() {
+    return Err(not_found());
+  }
+
+  let manifest = Manifest {
+    asset_url: Some(download_url.clone()),
+    ..manifest
+  };
+  let result = extract_and_finish(&path, download_url, portal, arch, integrity, manifest);
+  if cached
+    && matches!(
+      result,
+      Err(
+        Error::ChecksumMismatch { .. } | Error::BadSignature { .. } | Error::CantExtractFile { .. }
+      )
+    )
+  {
+    debug!("Removing {:?} from the download cache", path);
+    download_cache::remove(&path);
+  }
+  result
+}
+
+/// Extracts the archive at `path`, hashing it and verifying its signature as it is read, then installs it.
+fn extract_and_finish(
+  path: &Path,
+  download_url: &Url,
+  portal: DirectoryPortal<PathBuf>,
+  arch: PlatformArch,
+  integrity: Integrity<'_>,
+  manifest: Manifest,
+) -> Result<(), Error> {
+  debug!("Extracting {:?}...", path);
+  let file = File::open(path)?;
+  let mut verifying_reader = VerifyingReader::new(file, integrity.signature)?;
+  let mut reader = HashingReader::new(&mut verifying_reader);
+  Archive::extract_archive_into(portal.as_ref(), &mut reader, download_url.as_str())?;
+  debug!("Extraction completed");
+  let digest = reader.finish()?;
+  verifying_reader.finish()?;
+  finish_installation(portal, arch, digest, integrity.checksum, manifest)
+}
+
+/// Install a pact archive read from `reader`, such as a local file or stdin, into a directory
+#[allow(clippy::too_many_arguments)]
+pub fn install_pact_archive<P: AsRef<Path>>(
+  version: &Version,
+  reader: impl Read,
+  format: Archive,
+  installations_dir: P,
+  arch: PlatformArch,
+  force: bool,
+  expected: Option<&Checksum>,
+  manifest: Manifest,
+) -> Result<(), Error> {
+  let portal = create_portal(version, installations_dir.as_ref(), force)?;
+  debug!("Extracting {} archive...", format.as_str());
+  let mut reader = HashingReader::new(reader);
+  format.extract_into(portal.as_ref(), &mut reader)?;
+  debug!("Extraction completed");
+  finish_installation(portal, arch, reader.finish()?, expected, manifest)
+}
 
-  if version_installation_dir.exists() {
-    if force {
-      debug!("Removing directory {:?}", version_installation_dir);
-      std::fs::remove_dir_all(&version_installation_dir)?;
+/// Prepares a portal to extract `version` into, in a temporary directory next to its installation directory.
+///
+/// The caller holds the lock of `version`, see [`crate::lock::lock_version`].
+fn create_portal(
+  version: &Version,
+  installations_dir: &Path,
+  force: bool,
+) -> Result<DirectoryPortal<PathBuf>, Error> {
+  let version_installation_dir = installations_dir.join(version.v_str());
+  // Each version is extracted in its own directory, which only the process holding its lock uses.
+  // Cleaning it up first puts back an installation a crash left aside.
+  let temp_installations_dir = installations_dir.join(".downloads");
+  remove_abandoned_portals(&temp_installations_dir, installations_dir, version)?;
+
+  // With `force`, the installation is only replaced once the new one is ready, when teleporting.
+  if version_installation_dir.exists() && !force {
+    return Err(Error::VersionAlreadyInstalled {
+      path: version_installation_dir,
+    });
+  }
+  if !installations_dir.exists() {
+    debug!("Creating directory {:?}", installations_dir);
+    std::fs::create_dir_all(installations_dir)?;
+  }
+
+  let portals_dir = temp_installations_dir.join(version.v_str());
+  std::fs::create_dir_all(&portals_dir)?;
+
+  debug!("Creating directory portal");
+  Ok(DirectoryPortal::new_in(
+    &portals_dir,
+    version_installation_dir,
+  ))
+}
+
+/// Removes what the installations that were interrupted left in `temp_installations_dir`: the files
+/// of `version`, which the caller has locked, and those of the versions no process is installing.
+fn remove_abandoned_portals(
+  temp_installations_dir: &Path,
+  installations_dir: &Path,
+  version: &Version,
+) -> std::io::Result<()> {
+  let entries = match std::fs::read_dir(temp_installations_dir) {
+    Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(()),
+    entries => entries?,
+  };
+  for entry in entries {
+    let path = entry?.path();
+    let name = path.file_name().unwrap_or_default().to_string_lossy();
+    // Older versions of pactup extracted archives right into `temp_installations_dir`, in
+    // directories named like `.tmpXXXXXX`, which no lock covers.
+    let legacy = name.starts_with('.')
+      || !Version::parse(&name)
+        .is_ok_and(|parsed| parsed.is_installable() && parsed.v_str() == name);
+    let _lock = if legacy || name == version.v_str() {
+      None
+    } else {
+      // Holding the lock keeps another process from starting to install that version meanwhile.
+      match Lock::try_exclusive(&version_lock_path(installations_dir, &name))? {
+        Some(lock) => Some(lock),
+        None => continue,
+      }
+    };
+    debug!("Removing {:?}", path);
+    if path.is_dir() {
+      if !legacy {
+        // The previous installation of a version being replaced, when the process died before
+        // the new one took its place, is the only copy left.
+        restore_moved_aside(&path, &installations_dir.join(&*name))?;
+      }
+      std::fs::remove_dir_all(&path)?;
     } else {
-      return Err(Error::VersionAlreadyInstalled {
-        path: version_installation_dir,
-      });
+      std::fs::remove_file(&path)?;
     }
   }
-  if !installations_dir.as_ref().exists() {
-    debug!("Creating directory {:?}", installations_dir.as_ref());
-    std::fs::create_dir_all(installations_dir.as_ref())?;
+  Ok(())
+}
+
+/// Checks the archive and the extracted files, and moves them into place along with their manifest.
+fn finish_installation(
+  portal: DirectoryPortal<PathBuf>,
+  arch: PlatformArch,
+  digest: Sha256,
+  expected: Option<&Checksum>,
+  manifest: Manifest,
+) -> Result<(), Error> {
+  debug!("The archive SHA-256 digest is {}", digest);
+  if let Some(expected) = expected.filter(|expected| expected.digest != digest) {
+    return Err(Error::ChecksumMismatch {
+      expected: expected.clone(),
+      actual: digest,
+    });
   }
 
-  let temp_installations_dir = installations_dir.as_ref().join(".downloads");
-  if temp_installations_dir.exists() {
-    debug!("Removing directory {:?}", temp_installations_dir);
-    std::fs::remove_dir_all(&temp_installations_dir)?;
+  std::fs::read_dir(&portal)?
+    .next()
+    .ok_or(Error::TarIsEmpty)??;
+
+  // Dropping the portal on error removes the extracted files, leaving the previous installation, if
+  // any, untouched.
+  portal.ensure_bin_dir()?;
+  let binary = portal.join("bin").join("pact");
+  crate::elf::validate(&binary, arch)?.map_err(|source| Error::IncompatibleBinary { source })?;
+  let manifest = Manifest {
+    sha256: Some(digest),
+    size: Some(installed_versions::disk_usage(portal.as_ref())),
+    arch: Some(arch.to_string()),
+    ..manifest
   }
-  std::fs::create_dir_all(&temp_installations_dir)?;
+  .stamped(&binary);
+  installed_versions::write_manifest(portal.as_ref(), &manifest)?;
 
-  debug!("Creating directory portal");
-  let portal = DirectoryPortal::new_in(&temp_installations_dir, version_installation_dir);
+  portal.teleport()?;
+  Ok(())
+}
 
-  for _ in Archive::supported() {
-    debug!("Going to call for {}", download_url);
-    let response = crate::http::get(download_url.as_str())?;
+#[cfg(test)]
+mod tests {
+  use super::*;
 
-    if !response.status().is_success() {
-      continue;
+  #[test]
+  fn test_remove_abandoned_portals() {
+    let installations_dir = tempfile::tempdir().unwrap();
+    let temp_installations_dir = installations_dir.path().join(".downloads");
+    let version = Version::parse("5.1.0").unwrap();
+    for name in ["v5.1.0", "v4.13.0", ".tmpAbC123", "v5.0.0"] {
+      std::fs::create_dir_all(temp_installations_dir.join(name).join("bin")).unwrap();
     }
+    let lock_path = version_lock_path(installations_dir.path(), "v5.0.0");
+    let _lock = Lock::try_exclusive(&lock_path).unwrap().unwrap();
 
-    debug!("Extracting response...");
-    if show_progress {
-      Archive::extract_archive_into(
-        portal.as_ref(),
-        ResponseProgress::new(response, ProgressDrawTarget::stderr()),
-        download_url.as_str(),
-      )?;
-    } else {
-      Archive::extract_archive_into(portal.as_ref(), response, download_url.as_str())?;
-    }
-    debug!("Extraction completed");
-    std::fs::read_dir(&portal)?
-      .next()
-      .ok_or(Error::TarIsEmpty)??;
+    remove_abandoned_portals(&temp_installations_dir, installations_dir.path(), &version).unwrap();
+    let mut left: Vec<_> = std::fs::read_dir(&temp_installations_dir)
+      .unwrap()
+      .map(|entry| entry.unwrap().file_name())
+      .collect();
+    left.sort();
+    assert_eq!(left, vec!["v5.0.0"]);
+    let mut locks: Vec<_> = std::fs::read_dir(installations_dir.path().join(".locks"))
+      .unwrap()
+      .map(|entry| entry.unwrap().file_name())
+      .collect();
+    locks.sort();
+    assert_eq!(locks, vec!["v4.13.0.lock", "v5.0.0.lock"]);
+  }
 
-    portal.teleport()?;
+  #[test]
+  fn test_restores_the_installation_moved_aside_by_a_crash() {
+    let installations_dir = tempfile::tempdir().unwrap();
+    let version = Version::parse("5.1.0").unwrap();
+    // A teleport moved the previous installation aside, then the process died.
+    let aside = installations_dir
+      .path()
+      .join(".downloads")
+      .join("v5.1.0")
+      .join(".tmpAbC123.previous");
+    std::fs::create_dir_all(aside.join("bin")).unwrap();
+    std::fs::write(aside.join("bin").join("pact"), "pact").unwrap();
 
-    return Ok(());
+    let result = create_portal(&version, installations_dir.path(), false);
+    assert!(matches!(result, Err(Error::VersionAlreadyInstalled { .. })));
+    let binary = installations_dir
+      .path()
+      .join("v5.1.0")
+      .join("bin")
+      .join("pact");
+    assert_eq!(std::fs::read_to_string(binary).unwrap(), "pact");
   }
-
-  Err(Error::VersionNotFound {
-    version: version.clone(),
-    arch,
-  })
 }
@@ -0,0 +1,297 @@
+//! A minimal ELF reader, enough to tell which platform a binary was built for.
+
+use crate::system_info::PlatformArch;
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::Path;
+
+const MAGIC: &[u8; 4] = b"\x7fELF";
+const PT_INTERP: u32 = 3;
+
+const ELFOSABI_SYSV: u8 = 0;
+const ELFOSABI_GNU: u8 = 3;
+
+const EM_386: u16 = 3;
+const EM_PPC64: u16 = 21;
+const EM_S390: u16 = 22;
+const EM_ARM: u16 = 40;
+const EM_X86_64: u16 = 62;
+const EM_AARCH64: u16 = 183;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Class {
+  Elf32,
+  Elf64,
+}
+
+/// The identifying parts of an ELF header.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Header {
+  pub class: Class,
+  pub little_endian: bool,
+  pub os_abi: u8,
+  /// The `e_machine` field, e.g. `0x3e` for x86-64.
+  pub machine: u16,
+  program_headers_offset: u64,
+  program_header_size: u16,
+  program_headers: u16,
+}
+
+impl Header {
+  /// Parses the header out of the first bytes of a file, or `None` if it isn't an ELF file.
+  pub fn parse(bytes: &[u8]) -> Option<Self> {
+    if bytes.get(..4)? != MAGIC {
+      return None;
+    }
+    let class = match bytes.get(4)? {
+      1 => Class::Elf32,
+      2 => Class::Elf64,
+      _ => return None,
+    };
+    let little_endian = match bytes.get(5)? {
+      1 => true,
+      2 => false,
+      _ => return None,
+    };
+    let reader = Reader {
+      bytes,
+      little_endian,
+    };
+    let (program_headers_offset, program_header_size, program_headers) = match class {
+      Class::Elf32 => (u64::from(reader.u32(28)?), reader.u16(42)?, reader.u16(44)?),
+      Class::Elf64 => (reader.u64(32)?, reader.u16(54)?, reader.u16(56)?),
+    };
+    Some(Self {
+      class,
+      little_endian,
+      os_abi: *bytes.get(7)?,
+      machine: reader.u16(18)?,
+      program_headers_offset,
+      program_header_size,
+      program_headers,
+    })
+  }
+
+  /// The architecture the binary was built for, if pactup knows about it.
+  pub fn arch(&self) -> Option<PlatformArch> {
+    match (self.class, self.machine, self.little_endian) {
+      (Class::Elf32, EM_386, true) => Some(PlatformArch::X86),
+      (Class::Elf64, EM_X86_64, true) => Some(PlatformArch::X64),
+      (Class::Elf64, EM_AARCH64, true) => Some(PlatformArch::Arm64),
+      (Class::Elf32, EM_ARM, true) => Some(PlatformArch::Armv7l),
+      (Class::Elf64, EM_PPC64, true) => Some(PlatformArch::Ppc64le),
+      (Class::Elf64, EM_PPC64, false) => Some(PlatformArch::Ppc64),
+      (Class::Elf64, EM_S390, false) => Some(PlatformArch::S390x),
+      _ => None,
+    }
+  }
+
+  fn describe(&self) -> String {
+    let class = match self.class {
+      Class::Elf32 => 32,
+      Class::Elf64 => 64,
+    };
+    let endianness = if self.little_endian { "LE" } else { "BE" };
+    self.arch().map_or_else(
+      || format!("machine {:#x} ({class}-bit {endianness})", self.machine),
+      |arch| arch.to_string(),
+    )
+  }
+
+  /// Reads the header of the file at `path`. Returns `Ok(None)` when it isn't an ELF file.
+  pub fn read(path: &Path) -> std::io::Result<Option<Self>> {
+    let mut bytes = Vec::with_capacity(64);
+    File::open(path)?.take(64).read_to_end(&mut bytes)?;
+    Ok(Self::parse(&bytes))
+  }
+}
+
+/// The program interpreter (dynamic loader) of the ELF file at `path`, e.g. `/lib/ld-musl-x86_64.so.1`.
+/// Statically linked binaries don't have one.
+pub fn interpreter(path: &Path) -> std::io::Result<Option<String>> {
+  let Some(header) = Header::read(path)? else {
+    return Ok(None);
+  };
+  let mut file = File::open(path)?;
+  let mut program_headers =
+    vec![0; usize::from(header.program_header_size) * usize::from(header.program_headers)];
+  file.seek(SeekFrom::Start(header.program_headers_offset))?;
+  file.read_exact(&mut program_headers)?;
+
+  for entry in program_headers.chunks_exact(usize::from(header.program_header_size)) {
+    let reader = Reader {
+      bytes: entry,
+      little_endian: header.little_endian,
+    };
+    if reader.u32(0) != Some(PT_INTERP) {
+      continue;
+    }
+    let (offset, size) = match header.class {
+      Class::Elf32 => (reader.u32(4).map(u64::from), reader.u32(16).map(u64::from)),
+      Class::Elf64 => (reader.u64(8), reader.u64(32)),
+    };
+    let (Some(offset), Some(size)) = (offset, size) else {
+      return Ok(None);
+    };
+    let mut interpreter = vec![0; usize::try_from(size).unwrap_or_default()];
+    file.seek(SeekFrom::Start(offset))?;
+    file.read_exact(&mut interpreter)?;
+    let interpreter = String::from_utf8_lossy(&interpreter);
+    return Ok(Some(interpreter.trim_end_matches('\0').to_string()));
+  }
+  Ok(None)
+}
+
+/// Why a binary can't run on the requested architecture.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum Mismatch {
+  #[error("it was built for {found}, not {expected}")]
+  Arch {
+    expected: PlatformArch,
+    found: String,
+  },
+  #[error("it was built for another operating system (OS ABI {0})")]
+  OsAbi(u8),
+  #[error("it requires glibc ({0}), which musl systems don't provide")]
+  Glibc(String),
+}
+
+/// Checks that the ELF binary at `path` can run on `arch`.
+/// Files that aren't ELF binaries, like the executables of other operating systems, are not checked.
+pub fn validate(path: &Path, arch: PlatformArch) -> std::io::Result<Result<(), Mismatch>> {
+  let Some(header) = Header::read(path)? else {
+    return Ok(Ok(()));
+  };
+  let expected_arch = match arch {
+    PlatformArch::X64Musl => PlatformArch::X64,
+    arch => arch,
+  };
+  if header.arch() != Some(expected_arch) {
+    return Ok(Err(Mismatch::Arch {
+      expected: arch,
+      found: header.describe(),
+    }));
+  }
+  if ![ELFOSABI_SYSV, ELFOSABI_GNU].contains(&header.os_abi) {
+    return Ok(Err(Mismatch::OsAbi(header.os_abi)));
+  }
+  if arch == PlatformArch::X64Musl {
+    if let Some(interpreter) = interpreter(path)?.filter(|x| x.contains("ld-linux")) {
+      return Ok(Err(Mismatch::Glibc(interpreter)));
+    }
+  }
+  Ok(Ok(()))
+}
+
+struct Reader<'a> {
+  bytes: &'a [u8],
+  little_endian: bool,
+}
+
+impl Reader<'_> {
+  fn array<const N: usize>(&self, offset: usize) -> Option<[u8; N]> {
+    self.bytes.get(offset..offset + N)?.try_into().ok()
+  }
+
+  fn u16(&self, offset: usize) -> Option<u16> {
+    let bytes = self.array(offset)?;
+    Some(if self.little_endian {
+      u16::from_le_bytes(bytes)
+    } else {
+      u16::from_be_bytes(bytes)
+    })
+  }
+
+  fn u32(&self, offset: usize) -> Option<u32> {
+    let bytes = self.array(offset)?;
+    Some(if self.little_endian {
+      u32::from_le_bytes(bytes)
+    } else {
+      u32::from_be_bytes(bytes)
+    })
+  }
+
+  fn u64(&self, offset: usize) -> Option<u64> {
+    let bytes = self.array(offset)?;
+    Some(if self.little_endian {
+      u64::from_le_bytes(bytes)
+    } else {
+      u64::from_be_bytes(bytes)
+    })
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use pretty_assertions::assert_eq;
+
+  /// A 64-bit little endian ELF header for x86-64, with a single `PT_INTERP` program header.
+  fn x64_elf(interpreter: &str) -> Vec<u8> {
+    let mut bytes = vec![0; 64 + 56];
+    bytes[..4].copy_from_slice(MAGIC);
+    bytes[4] = 2;
+    bytes[5] = 1;
+    bytes[18..20].copy_from_slice(&0x3e_u16.to_le_bytes());
+    bytes[32..40].copy_from_slice(&64_u64.to_le_bytes());
+    bytes[54..56].copy_from_slice(&56_u16.to_le_bytes());
+    bytes[56..58].copy_from_slice(&1_u16.to_le_bytes());
+    bytes[64..68].copy_from_slice(&PT_INTERP.to_le_bytes());
+    bytes[72..80].copy_from_slice(&120_u64.to_le_bytes());
+    bytes[96..104].copy_from_slice(&(interpreter.len() as u64 + 1).to_le_bytes());
+    bytes.extend(interpreter.as_bytes());
+    bytes.push(0);
+    bytes
+  }
+
+  #[test]
+  fn test_parse_header() {
+    let header = Header::parse(&x64_elf("/lib64/ld-linux-x86-64.so.2")).unwrap();
+    assert_eq!(header.class, Class::Elf64);
+    assert!(header.little_endian);
+    assert_eq!(header.machine, 0x3e);
+    assert_eq!(Header::parse(b"#!/bin/sh\n"), None);
+  }
+
+  #[test]
+  fn test_validate() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("pact");
+    std::fs::write(&path, x64_elf("/lib64/ld-linux-x86-64.so.2")).unwrap();
+
+    assert_eq!(validate(&path, PlatformArch::X64).unwrap(), Ok(()));
+    assert_eq!(
+      validate(&path, PlatformArch::Arm64).unwrap(),
+      Err(Mismatch::Arch {
+        expected: PlatformArch::Arm64,
+        found: "x64".to_string()
+      })
+    );
+    assert_eq!(
+      validate(&path, PlatformArch::X64Musl).unwrap(),
+      Err(Mismatch::Glibc("/lib64/ld-linux-x86-64.so.2".to_string()))
+    );
+
+    let mut freebsd = x64_elf("/libexec/ld-elf.so.1");
+    freebsd[7] = 9;
+    std::fs::write(&path, freebsd).unwrap();
+    assert_eq!(
+      validate(&path, PlatformArch::X64).unwrap(),
+      Err(Mismatch::OsAbi(9))
+    );
+
+    std::fs::write(&path, "#!/bin/sh\nexec pact-wrapped \"$@\"\n").unwrap();
+    assert_eq!(validate(&path, PlatformArch::Arm64).unwrap(), Ok(()));
+  }
+
+  #[test]
+  fn test_interpreter() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("sh");
+    std::fs::write(&path, x64_elf("/lib/ld-musl-x86_64.so.1")).unwrap();
+    assert_eq!(
+      interpreter(&path).unwrap().as_deref(),
+      Some("/lib/ld-musl-x86_64.so.1")
+    );
+  }
+}
@@ -12,6 +12,18 @@ pub fn symlink_dir<P: AsRef<Path>, U: AsRef<Path>>(from: P, to: U) -> std::io::R
   Ok(())
 }
 
+#[cfg(unix)]
+pub fn symlink_file<P: AsRef<Path>, U: AsRef<Path>>(from: P, to: U) -> std::io::Result<()> {
+  std::os::unix::fs::symlink(from, to)?;
+  Ok(())
+}
+
+#[cfg(windows)]
+pub fn symlink_file<P: AsRef<Path>, U: AsRef<Path>>(from: P, to: U) -> std::io::Result<()> {
+  std::os::windows::fs::symlink_file(from, to)?;
+  Ok(())
+}
+
 #[cfg(windows)]
 pub fn remove_symlink_dir<P: AsRef<Path>>(path: P) -> std::io::Result<()> {
   std::fs::remove_dir(path)?;
@@ -24,6 +36,88 @@ pub fn remove_symlink_dir<P: AsRef<Path>>(path: P) -> std::io::Result<()> {
   Ok(())
 }
 
+/// Points the symlink at `to` to `from`, replacing the one already there, if any.
+///
+/// The new symlink is created under a temporary name and renamed over the old one, which is atomic,
+/// so that `to` never goes missing for the programs resolving it meanwhile.
+#[cfg(unix)]
+pub fn replace_symlink_dir<P: AsRef<Path>, U: AsRef<Path>>(from: P, to: U) -> std::io::Result<()> {
+  use std::sync::atomic::{AtomicUsize, Ordering};
+  static COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+  let to = to.as_ref();
+  let file_name = to
+    .file_name()
+    .ok_or_else(|| std::io::Error::from(std::io::ErrorKind::InvalidInput))?;
+  let mut temp_name = std::ffi::OsString::from(".");
+  temp_name.push(file_name);
+  temp_name.push(format!(
+    ".{}-{}.tmp",
+    std::process::id(),
+    COUNTER.fetch_add(1, Ordering::Relaxed)
+  ));
+  let temp_path = to.with_file_name(temp_name);
+  symlink_dir(from, &temp_path)?;
+  std::fs::rename(&temp_path, to).inspect_err(|_| {
+    std::fs::remove_file(&temp_path).ok();
+  })
+}
+
+/// Points the junction at `to` to `from`, replacing the one already there, if any.
+///
+/// A junction can't be renamed over another one, so the old one is removed first. If the new one
+/// can't be created, the error removing the old one comes first, if any.
+#[cfg(windows)]
+pub fn replace_symlink_dir<P: AsRef<Path>, U: AsRef<Path>>(from: P, to: U) -> std::io::Result<()> {
+  let removal = remove_symlink_dir(&to);
+  symlink_dir(from, to).or_else(|err| removal.and(Err(err)))
+}
+
 pub fn shallow_read_symlink<P: AsRef<Path>>(path: P) -> std::io::Result<std::path::PathBuf> {
   std::fs::read_link(path)
 }
+
+/// The space available to the current user on the disk holding `path`, in bytes.
+///
+/// `path` doesn't have to exist yet: its closest existing ancestor is used instead.
+/// Returns `None` when the disk can't be determined.
+pub fn available_space<P: AsRef<Path>>(path: P) -> Option<u64> {
+  let path = path
+    .as_ref()
+    .ancestors()
+    .find_map(|ancestor| ancestor.canonicalize().ok())?;
+  let disks = sysinfo::Disks::new_with_refreshed_list();
+  disks
+    .list()
+    .iter()
+    .filter(|disk| path.starts_with(disk.mount_point()))
+    .max_by_key(|disk| disk.mount_point().as_os_str().len())
+    .map(sysinfo::Disk::available_space)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_available_space_of_missing_path() {
+    let dir = tempfile::tempdir().unwrap();
+    let missing = dir.path().join("not").join("created");
+    assert_eq!(available_space(&missing), available_space(dir.path()));
+  }
+
+  #[test]
+  fn test_replace_symlink_dir() {
+    let dir = tempfile::tempdir().unwrap();
+    let (old, new) = (dir.path().join("old"), dir.path().join("new"));
+    std::fs::create_dir_all(&old).unwrap();
+    std::fs::create_dir_all(&new).unwrap();
+    let link = dir.path().join("link");
+
+    replace_symlink_dir(&old, &link).unwrap();
+    assert_eq!(shallow_read_symlink(&link).unwrap(), old);
+    replace_symlink_dir(&new, &link).unwrap();
+    assert_eq!(shallow_read_symlink(&link).unwrap(), new);
+    assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 3);
+  }
+}
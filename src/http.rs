@@ -1,21 +1,463 @@
 //! This module is an adapter for HTTP related operations.
 //! In the future, if we want to migrate to a different HTTP library,
 //! we can easily change this facade instead of multiple places in the crate.
+//!
+//! Besides `http(s)://` URLs, `file://` URLs are served from the local file system,
+//! so release indexes and their assets can live on disk or a mounted share.
+//!
+//! Requests go through a single client, set up once from the [`Settings`] given to [`configure`].
+//! Connection errors, server errors and rate limited responses are retried with an exponential
+//! backoff, honoring the `Retry-After` and `X-RateLimit-*` headers sent by GitHub.
 
-use reqwest::{blocking::Client, IntoUrl};
+use chrono::{DateTime, Utc};
+use log::debug;
+use reqwest::blocking::{Client, ClientBuilder};
+use reqwest::{redirect, Certificate, Method, NoProxy, Proxy, ResponseBuilderExt};
+use std::path::PathBuf;
+use std::sync::OnceLock;
+use std::time::Duration;
+use url::Url;
+
+pub use reqwest::{header, StatusCode};
 
 #[derive(Debug, thiserror::Error, miette::Diagnostic)]
-#[error(transparent)]
-#[diagnostic(code("pactup::http::error"))]
-pub struct Error(#[from] reqwest::Error);
+pub enum Error {
+  #[error(transparent)]
+  #[diagnostic(code("pactup::http::error"))]
+  Request(#[from] reqwest::Error),
+  #[error("can't read {}: {}", path.display(), source)]
+  #[diagnostic(code("pactup::http::file_error"))]
+  File {
+    path: PathBuf,
+    source: std::io::Error,
+  },
+  #[error("can't read the CA bundle {}: {}", path.display(), source)]
+  #[diagnostic(code("pactup::http::ca_bundle_error"))]
+  CaBundle {
+    path: PathBuf,
+    source: std::io::Error,
+  },
+  #[error("invalid CA bundle {}: {}", path.display(), source)]
+  #[diagnostic(code("pactup::http::invalid_ca_bundle"))]
+  InvalidCaBundle {
+    path: PathBuf,
+    source: reqwest::Error,
+  },
+  #[error("{url} is rate limited until {} UTC. Try again later, or for GitHub, set a token with `--github-token`.", until.format("%H:%M:%S"))]
+  #[diagnostic(code("pactup::http::rate_limited"))]
+  RateLimited { url: String, until: DateTime<Utc> },
+}
 pub type Response = reqwest::blocking::Response;
 
-pub fn get(url: impl IntoUrl) -> Result<Response, Error> {
-  Ok(
-    Client::new()
-      .get(url)
+/// How requests are sent, set from the configuration with [`configure`].
+#[derive(Debug, Clone)]
+pub struct Settings {
+  /// How long to wait for a connection to be established.
+  pub connect_timeout: Option<Duration>,
+  /// How long to wait for the server to answer, or for the next chunk of a response.
+  pub read_timeout: Option<Duration>,
+  /// How many times a failed request is tried again.
+  pub retries: u32,
+  /// The delay before the first retry, doubled on every following one.
+  pub retry_delay: Duration,
+  /// The longest a rate limited request waits for the limit to reset, before giving up.
+  pub max_rate_limit_wait: Duration,
+  /// A proxy for all requests, instead of the `HTTPS_PROXY` and `HTTP_PROXY` environment variables.
+  /// `NO_PROXY` is honored either way.
+  pub proxy: Option<Url>,
+  /// A PEM file of certificates to trust, besides the system ones.
+  pub ca_bundle: Option<PathBuf>,
+  /// Follow redirects from `https://` to plain `http://` URLs.
+  pub allow_http_redirects: bool,
+}
+
+impl Default for Settings {
+  fn default() -> Self {
+    Self {
+      connect_timeout: Some(Duration::from_secs(10)),
+      read_timeout: Some(Duration::from_secs(30)),
+      retries: 3,
+      retry_delay: Duration::from_millis(500),
+      max_rate_limit_wait: Duration::from_secs(60),
+      proxy: None,
+      ca_bundle: None,
+      allow_http_redirects: false,
+    }
+  }
+}
+
+/// The longest delay between two retries, unless the server asks for more.
+const MAX_RETRY_DELAY: Duration = Duration::from_secs(30);
+
+/// The most redirects followed for a single request.
+const MAX_REDIRECTS: usize = 10;
+
+static SETTINGS: OnceLock<Settings> = OnceLock::new();
+
+/// Sets how requests are sent. Only the first call has an effect, and only before the first request.
+pub fn configure(settings: Settings) {
+  if SETTINGS.set(settings).is_err() {
+    debug!("The HTTP settings were already set");
+  }
+}
+
+fn settings() -> &'static Settings {
+  SETTINGS.get_or_init(Settings::default)
+}
+
+/// A client shared by all requests, so connections are pooled and reused,
+/// including across threads.
+fn client() -> Result<&'static Client, Error> {
+  static CLIENT: OnceLock<Client> = OnceLock::new();
+  if let Some(client) = CLIENT.get() {
+    return Ok(client);
+  }
+  let client = build_client(settings())?;
+  Ok(CLIENT.get_or_init(|| client))
+}
+
+fn build_client(settings: &Settings) -> Result<Client, Error> {
+  let allow_http_redirects = settings.allow_http_redirects;
+  let mut builder = ClientBuilder::new()
+    .connect_timeout(settings.connect_timeout)
+    .timeout(settings.read_timeout)
+    .redirect(redirect::Policy::custom(move |attempt| {
+      if !allow_http_redirects && is_downgrade(attempt.previous(), attempt.url()) {
+        let message = format!(
+          "refusing to follow a redirect to {}, which isn't encrypted. Use `--allow-http-redirects` to follow it anyway.",
+          attempt.url()
+        );
+        attempt.error(message)
+      } else if attempt.previous().len() > MAX_REDIRECTS {
+        attempt.error("too many redirects")
+      } else {
+        attempt.follow()
+      }
+    }));
+  if let Some(proxy) = &settings.proxy {
+    builder = builder.proxy(Proxy::all(proxy.as_str())?.no_proxy(NoProxy::from_env()));
+  }
+  if let Some(path) = &settings.ca_bundle {
+    let pem = std::fs::read(path).map_err(|source| Error::CaBundle {
+      path: path.clone(),
+      source,
+    })?;
+    let certificates =
+      Certificate::from_pem_bundle(&pem).map_err(|source| Error::InvalidCaBundle {
+        path: path.clone(),
+        source,
+      })?;
+    for certificate in certificates {
+      builder = builder.add_root_certificate(certificate);
+    }
+  }
+  Ok(builder.build()?)
+}
+
+/// Whether following a redirect to `next` drops the encryption of one of the `previous` URLs.
+fn is_downgrade(previous: &[Url], next: &Url) -> bool {
+  next.scheme() == "http" && previous.iter().any(|url| url.scheme() == "https")
+}
+
+#[cfg(test)]
+pub fn get(url: impl AsRef<str>) -> Result<Response, Error> {
+  get_with_headers(url, &header::HeaderMap::new())
+}
+
+/// Sends a `GET` request with the given headers. Headers are ignored for `file://` URLs.
+pub fn get_with_headers(
+  url: impl AsRef<str>,
+  headers: &header::HeaderMap,
+) -> Result<Response, Error> {
+  let url = url.as_ref();
+  if let Some(file_url) = Url::parse(url).ok().filter(|x| x.scheme() == "file") {
+    return get_file(file_url);
+  }
+  send(client()?, settings(), &Method::GET, url, headers)
+}
+
+/// Sends a `HEAD` request with the given headers, to learn about a file without downloading it.
+/// `file://` URLs aren't supported.
+pub fn head_with_headers(
+  url: impl AsRef<str>,
+  headers: &header::HeaderMap,
+) -> Result<Response, Error> {
+  send(client()?, settings(), &Method::HEAD, url.as_ref(), headers)
+}
+
+/// Sends a request, trying it again on failures that may be transient.
+/// Once out of retries, the last response is returned as is, for the caller to report.
+fn send(
+  client: &Client,
+  settings: &Settings,
+  method: &Method,
+  url: &str,
+  headers: &header::HeaderMap,
+) -> Result<Response, Error> {
+  let mut attempt = 0;
+  loop {
+    let result = client
+      .request(method.clone(), url)
       // Some sites require a user agent.
       .header("User-Agent", concat!("pactup ", env!("CARGO_PKG_VERSION")))
-      .send()?,
-  )
+      .headers(headers.clone())
+      .send();
+    let delay = match &result {
+      Ok(response) => retry_delay(response, attempt, settings)?,
+      Err(err) if err.is_connect() || err.is_timeout() => Some(backoff(attempt, settings)),
+      Err(_) => None,
+    };
+    match delay {
+      Some(delay) if attempt < settings.retries => {
+        debug!(
+          "Request to {} failed ({}), trying again in {:?}",
+          url,
+          match &result {
+            Ok(response) => response.status().to_string(),
+            Err(err) => err.to_string(),
+          },
+          delay
+        );
+        std::thread::sleep(delay);
+        attempt += 1;
+      }
+      _ => return Ok(result?),
+    }
+  }
+}
+
+/// How long to wait before retrying the request that got `response`, or `None` if it shouldn't be retried.
+fn retry_delay(
+  response: &Response,
+  attempt: u32,
+  settings: &Settings,
+) -> Result<Option<Duration>, Error> {
+  let headers = response.headers();
+  let status = response.status();
+  let rate_limited = status == StatusCode::TOO_MANY_REQUESTS
+    || (status == StatusCode::FORBIDDEN
+      && header_value(headers, "x-ratelimit-remaining").as_deref() == Some("0"));
+  if !rate_limited && !status.is_server_error() {
+    return Ok(None);
+  }
+
+  let now = Utc::now();
+  let until = retry_after(headers, now).or_else(|| {
+    let reset = header_value(headers, "x-ratelimit-reset").filter(|_| rate_limited)?;
+    DateTime::from_timestamp(reset.parse().ok()?, 0)
+  });
+  let delay = until.map_or_else(
+    || backoff(attempt, settings),
+    |until| (until - now).to_std().unwrap_or_default(),
+  );
+  if !rate_limited {
+    // Server errors are usually short-lived, whatever the server asks for.
+    return Ok(Some(delay.min(MAX_RETRY_DELAY)));
+  }
+  if delay > settings.max_rate_limit_wait {
+    return Err(Error::RateLimited {
+      url: response.url().to_string(),
+      until: until.unwrap_or(now),
+    });
+  }
+  Ok(Some(delay))
+}
+
+/// The time a `Retry-After` header asks to wait until, given in seconds or as an HTTP date.
+fn retry_after(headers: &header::HeaderMap, now: DateTime<Utc>) -> Option<DateTime<Utc>> {
+  let value = header_value(headers, header::RETRY_AFTER.as_str())?;
+  match value.parse::<u32>() {
+    Ok(seconds) => Some(now + chrono::Duration::seconds(seconds.into())),
+    Err(_) => DateTime::parse_from_rfc2822(&value)
+      .ok()
+      .map(|date| date.with_timezone(&Utc)),
+  }
+}
+
+fn header_value(headers: &header::HeaderMap, name: &str) -> Option<String> {
+  Some(headers.get(name)?.to_str().ok()?.trim().to_string())
+}
+
+fn backoff(attempt: u32, settings: &Settings) -> Duration {
+  settings
+    .retry_delay
+    .saturating_mul(2_u32.saturating_pow(attempt))
+    .min(MAX_RETRY_DELAY)
+}
+
+fn get_file(url: Url) -> Result<Response, Error> {
+  let path = url.to_file_path().map_err(|()| Error::File {
+    path: PathBuf::from(url.path()),
+    source: std::io::ErrorKind::InvalidInput.into(),
+  })?;
+  let body = std::fs::read(&path).map_err(|source| Error::File {
+    path: path.clone(),
+    source,
+  })?;
+  let response = ::http::Response::builder()
+    .url(url)
+    .header(header::CONTENT_LENGTH, body.len())
+    .body(body)
+    .expect("Can't build a response for a local file");
+  Ok(response.into())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use pretty_assertions::assert_eq;
+
+  #[test]
+  fn test_get_file_url() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("index.json");
+    std::fs::write(&path, "{}").unwrap();
+    let url = Url::from_file_path(&path).unwrap();
+
+    let response = get(url.as_str()).unwrap();
+    assert!(response.status().is_success());
+    assert_eq!(response.content_length(), Some(2));
+    assert_eq!(response.url(), &url);
+    assert_eq!(response.text().unwrap(), "{}");
+  }
+
+  /// Serves the given raw responses, one per connection, on a local port.
+  fn serve(responses: &[&str]) -> (String, std::thread::JoinHandle<()>) {
+    use std::io::{BufRead, BufReader, Write};
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}/releases", listener.local_addr().unwrap());
+    let responses: Vec<String> = responses.iter().map(ToString::to_string).collect();
+    let handle = std::thread::spawn(move || {
+      for response in responses {
+        let (mut stream, _) = listener.accept().unwrap();
+        let mut reader = BufReader::new(stream.try_clone().unwrap());
+        let mut line = String::new();
+        while reader.read_line(&mut line).unwrap() > 2 {
+          line.clear();
+        }
+        write!(
+          stream,
+          "{response}\r\nConnection: close\r\nContent-Length: 2\r\n\r\nok"
+        )
+        .unwrap();
+      }
+    });
+    (url, handle)
+  }
+
+  fn test_settings() -> Settings {
+    Settings {
+      retry_delay: Duration::from_millis(1),
+      ..Settings::default()
+    }
+  }
+
+  #[test]
+  fn test_retries_server_errors() {
+    let (url, server) = serve(&[
+      "HTTP/1.1 503 Service Unavailable\r\nRetry-After: 0",
+      "HTTP/1.1 502 Bad Gateway",
+      "HTTP/1.1 200 OK",
+    ]);
+    let settings = test_settings();
+    let client = build_client(&settings).unwrap();
+    let response = send(
+      &client,
+      &settings,
+      &Method::GET,
+      &url,
+      &header::HeaderMap::new(),
+    )
+    .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.text().unwrap(), "ok");
+    server.join().unwrap();
+  }
+
+  #[test]
+  fn test_returns_the_last_response_once_out_of_retries() {
+    let (url, server) = serve(&["HTTP/1.1 500 Internal Server Error"; 2]);
+    let settings = Settings {
+      retries: 1,
+      ..test_settings()
+    };
+    let client = build_client(&settings).unwrap();
+    let response = send(
+      &client,
+      &settings,
+      &Method::GET,
+      &url,
+      &header::HeaderMap::new(),
+    )
+    .unwrap();
+    assert_eq!(response.status(), StatusCode::INTERNAL_SERVER_ERROR);
+    server.join().unwrap();
+  }
+
+  #[test]
+  fn test_rate_limits() {
+    let reset = Utc::now().timestamp() + 3600;
+    let (url, server) = serve(&[
+      "HTTP/1.1 429 Too Many Requests\r\nRetry-After: 0",
+      &format!("HTTP/1.1 403 Forbidden\r\nX-RateLimit-Remaining: 0\r\nX-RateLimit-Reset: {reset}"),
+    ]);
+    let settings = test_settings();
+    let client = build_client(&settings).unwrap();
+    let result = send(
+      &client,
+      &settings,
+      &Method::GET,
+      &url,
+      &header::HeaderMap::new(),
+    );
+    assert!(matches!(result, Err(Error::RateLimited { until, .. }) if until.timestamp() == reset));
+    server.join().unwrap();
+  }
+
+  #[test]
+  fn test_caps_the_delay_of_server_errors() {
+    let response = |headers: &[(&str, &str)]| {
+      let mut builder = ::http::Response::builder().status(StatusCode::SERVICE_UNAVAILABLE);
+      for (name, value) in headers {
+        builder = builder.header(*name, *value);
+      }
+      Response::from(builder.body(Vec::<u8>::new()).unwrap())
+    };
+    let settings = test_settings();
+    assert_eq!(
+      retry_delay(&response(&[("Retry-After", "86400")]), 0, &settings).unwrap(),
+      Some(MAX_RETRY_DELAY)
+    );
+    // Only rate limits are reset at a given time.
+    let reset = (Utc::now().timestamp() + 86400).to_string();
+    assert_eq!(
+      retry_delay(&response(&[("X-RateLimit-Reset", &reset)]), 0, &settings).unwrap(),
+      Some(settings.retry_delay)
+    );
+  }
+
+  #[test]
+  fn test_is_downgrade() {
+    let url = |url: &str| Url::parse(url).unwrap();
+    assert!(is_downgrade(
+      &[url("https://github.com/pact.tar.gz")],
+      &url("http://mirror.example.com/pact.tar.gz")
+    ));
+    assert!(!is_downgrade(
+      &[url("http://mirror.example.com/pact.tar.gz")],
+      &url("http://mirror.example.com/v2/pact.tar.gz")
+    ));
+    assert!(!is_downgrade(
+      &[url("https://github.com/pact.tar.gz")],
+      &url("https://objects.githubusercontent.com/pact.tar.gz")
+    ));
+  }
+
+  #[test]
+  fn test_get_missing_file_url() {
+    let dir = tempfile::tempdir().unwrap();
+    let url = Url::from_file_path(dir.path().join("missing.json")).unwrap();
+    let result = get(url.as_str());
+    assert!(matches!(result, Err(Error::File { .. })));
+  }
 }
@@ -1,6 +1,15 @@
+use crate::checksum::Sha256;
 use crate::version::Version;
-use std::path::Path;
+use chrono::{DateTime, Utc};
+use log::debug;
+use serde::{Deserialize, Serialize};
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::mpsc;
+use std::time::{Duration, Instant};
 use thiserror::Error;
+use url::Url;
 
 pub fn list<P: AsRef<Path>>(installations_dir: P) -> Result<Vec<Version>, Error> {
   let mut vec = vec![];
@@ -26,6 +35,162 @@ pub fn list<P: AsRef<Path>>(installations_dir: P) -> Result<Vec<Version>, Error>
   Ok(vec)
 }
 
+/// An installed version, along with what was recorded when it was installed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Installation {
+  pub version: Version,
+  pub manifest: Manifest,
+}
+
+/// Lists the installed versions, reading the manifest of each.
+pub fn list_installations<P: AsRef<Path>>(
+  installations_dir: P,
+) -> Result<Vec<Installation>, Error> {
+  let installations_dir = installations_dir.as_ref();
+  Ok(
+    list(installations_dir)?
+      .into_iter()
+      .map(|version| Installation {
+        manifest: read_manifest(&installations_dir.join(version.v_str())),
+        version,
+      })
+      .collect(),
+  )
+}
+
+/// The file, in an installation directory, recording where the version came from and how it was installed.
+const MANIFEST_FILE: &str = ".pactup-install.json";
+
+/// What is recorded about an installation. Every field is optional, as not everything is known about
+/// every installation, e.g. where an archive read from stdin came from.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Manifest {
+  /// The name of the source the version was installed from.
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub source: Option<String>,
+  /// The URL of the archive, unless it was read from a local file or stdin.
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub asset_url: Option<Url>,
+  /// The Linux variant of the asset, such as `22.04` or `musl`, when its name tells one.
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub variant: Option<String>,
+  /// The SHA-256 digest of the archive.
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub sha256: Option<Sha256>,
+  /// The disk space used by the installation, in bytes.
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub size: Option<u64>,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub installed_at: Option<DateTime<Utc>>,
+  /// The architecture the version was installed for.
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub arch: Option<String>,
+  /// The version of pactup that installed the version.
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub pactup_version: Option<String>,
+  /// What `pact --version` printed once the version was installed.
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub pact_version: Option<String>,
+  /// The external binary a linked version points to. pactup didn't install it, and must not delete it.
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub link: Option<PathBuf>,
+}
+
+impl Manifest {
+  /// Fills in what is known about any installation made now, by this pactup, of the `pact` `binary`.
+  pub fn stamped(self, binary: &Path) -> Self {
+    Self {
+      installed_at: Some(Utc::now()),
+      pactup_version: Some(env!("CARGO_PKG_VERSION").to_string()),
+      pact_version: pact_version(binary),
+      ..self
+    }
+  }
+}
+
+/// Writes the manifest of the version in `installation_dir`.
+pub fn write_manifest(installation_dir: &Path, manifest: &Manifest) -> std::io::Result<()> {
+  let text = serde_json::to_string_pretty(manifest).expect("Can't serialize the manifest");
+  std::fs::write(installation_dir.join(MANIFEST_FILE), text)
+}
+
+/// The manifest of the version in `installation_dir`, empty for versions installed before manifests
+/// were written.
+pub fn read_manifest(installation_dir: &Path) -> Manifest {
+  std::fs::read_to_string(installation_dir.join(MANIFEST_FILE))
+    .ok()
+    .and_then(|text| serde_json::from_str(&text).ok())
+    .unwrap_or_default()
+}
+
+/// The external binary the version in `installation_dir` links to, or `None` if pactup installed it.
+pub fn link_target(installation_dir: &Path) -> Option<PathBuf> {
+  read_manifest(installation_dir).link
+}
+
+/// The disk space used by the files in `dir`, without following links.
+pub fn disk_usage(dir: &Path) -> u64 {
+  walkdir::WalkDir::new(dir)
+    .into_iter()
+    .filter_map(Result::ok)
+    .filter_map(|entry| entry.metadata().ok())
+    .filter(std::fs::Metadata::is_file)
+    .map(|metadata| metadata.len())
+    .sum()
+}
+
+/// How long `pact --version` may take, so a binary that hangs doesn't hang the installation.
+const PACT_VERSION_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// The first line `binary --version` prints, if it runs and succeeds in time.
+fn pact_version(binary: &Path) -> Option<String> {
+  let mut child = Command::new(binary)
+    .arg("--version")
+    .stdin(Stdio::null())
+    .stdout(Stdio::piped())
+    .stderr(Stdio::null())
+    .spawn()
+    .map_err(|err| debug!("Can't run {:?}: {}", binary, err))
+    .ok()?;
+  // The output is read as it comes, so that a binary printing more than a pipe can hold doesn't
+  // block until it is killed.
+  let mut stdout = child.stdout.take()?;
+  let (sender, receiver) = mpsc::channel();
+  std::thread::spawn(move || {
+    let mut output = vec![];
+    let result = stdout.read_to_end(&mut output).map(|_| output);
+    sender.send(result).ok();
+  });
+  let started = Instant::now();
+  let status = loop {
+    match child.try_wait() {
+      Ok(Some(status)) => break status,
+      Ok(None) if started.elapsed() < PACT_VERSION_TIMEOUT => {
+        std::thread::sleep(Duration::from_millis(20));
+      }
+      _ => {
+        debug!("{:?} --version didn't finish in time", binary);
+        child.kill().ok();
+        child.wait().ok();
+        return None;
+      }
+    }
+  };
+  if !status.success() {
+    return None;
+  }
+  // A process the binary started may still hold the pipe open.
+  let remaining = PACT_VERSION_TIMEOUT.saturating_sub(started.elapsed());
+  let output = receiver.recv_timeout(remaining).ok()?.ok()?;
+  let output = String::from_utf8_lossy(&output);
+  let line = output
+    .lines()
+    .map(str::trim)
+    .find(|line| !line.is_empty())?;
+  Some(line.to_string())
+}
+
 #[derive(Debug, Error)]
 pub enum Error {
   #[error(transparent)]
@@ -39,3 +204,62 @@ pub enum Error {
     source: node_semver::SemverError,
   },
 }
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use pretty_assertions::assert_eq;
+
+  #[test]
+  fn test_manifest_round_trip() {
+    let dir = tempfile::tempdir().unwrap();
+    assert_eq!(read_manifest(dir.path()), Manifest::default());
+    let manifest = Manifest {
+      source: Some("pact-5".to_string()),
+      asset_url: Some(
+        "https://example.com/pact-5.1.0-linux-x64-22.04.tar.gz"
+          .parse()
+          .unwrap(),
+      ),
+      variant: Some("22.04".to_string()),
+      sha256: Some(Sha256::of(b"pact")),
+      size: Some(42),
+      arch: Some("x64".to_string()),
+      ..Manifest::default()
+    }
+    .stamped(&dir.path().join("missing"));
+    assert_eq!(manifest.pact_version, None);
+    assert!(manifest.installed_at.is_some());
+
+    write_manifest(dir.path(), &manifest).unwrap();
+    assert_eq!(read_manifest(dir.path()), manifest);
+  }
+
+  #[test]
+  #[cfg(unix)]
+  fn test_pact_version() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let dir = tempfile::tempdir().unwrap();
+    let binary = dir.path().join("pact");
+    std::fs::write(&binary, "#!/bin/sh\necho\necho 'pact version 5.1.0'\n").unwrap();
+    std::fs::set_permissions(&binary, std::fs::Permissions::from_mode(0o755)).unwrap();
+    assert_eq!(
+      pact_version(&binary),
+      Some("pact version 5.1.0".to_string())
+    );
+
+    // More output than a pipe holds, which must not block the binary until it times out.
+    std::fs::write(
+      &binary,
+      "#!/bin/sh\necho 'pact version 5.1.0'\nhead -c 1000000 /dev/zero\n",
+    )
+    .unwrap();
+    let started = Instant::now();
+    assert_eq!(
+      pact_version(&binary),
+      Some("pact version 5.1.0".to_string())
+    );
+    assert!(started.elapsed() < PACT_VERSION_TIMEOUT);
+  }
+}
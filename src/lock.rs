@@ -0,0 +1,190 @@
+//! Advisory file locks, so that pactup processes sharing an installations directory don't get in
+//! each other's way.
+//!
+//! The locks are files in the `.locks` directory of the installations directory:
+//! `<version>.lock` is held exclusively while a version is installed or uninstalled, and
+//! `store.lock` is held shared by installations, and exclusively by the commands removing the
+//! downloads they may be using, such as `pactup cache prune`.
+//!
+//! The operating system releases the locks of a process when it exits, even when it crashes, so
+//! being able to take a lock tells that the process which held it is gone.
+
+use crate::config::PactupConfig;
+use crate::outln;
+use crate::version::Version;
+use colored::Colorize;
+use fs4::fs_std::FileExt;
+use log::debug;
+use std::fs::File;
+use std::io::{Seek, Write};
+use std::path::{Path, PathBuf};
+
+/// Whether a lock can be held by several processes at once.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Mode {
+  Shared,
+  Exclusive,
+}
+
+/// A lock, released when dropped.
+#[derive(Debug)]
+pub struct Lock {
+  file: File,
+  mode: Mode,
+}
+
+impl Lock {
+  /// Takes the lock at `path`, waiting for the processes holding it to release it. `waiting` is
+  /// called before waiting, with the ID of the process holding the lock exclusively, if known.
+  pub fn acquire(
+    path: &Path,
+    mode: Mode,
+    waiting: impl FnOnce(Option<u32>),
+  ) -> std::io::Result<Self> {
+    let file = open(path)?;
+    if !try_lock(&file, mode)? {
+      waiting(holder(path));
+      debug!("Waiting for the lock {:?}", path);
+      match mode {
+        Mode::Shared => FileExt::lock_shared(&file)?,
+        Mode::Exclusive => FileExt::lock_exclusive(&file)?,
+      }
+    }
+    Self::locked(file, mode)
+  }
+
+  /// Takes the lock at `path` exclusively, unless another process, or another handle of this one, holds it.
+  pub fn try_exclusive(path: &Path) -> std::io::Result<Option<Self>> {
+    let file = open(path)?;
+    if !try_lock(&file, Mode::Exclusive)? {
+      return Ok(None);
+    }
+    Self::locked(file, Mode::Exclusive).map(Some)
+  }
+
+  fn locked(mut file: File, mode: Mode) -> std::io::Result<Self> {
+    // Tells the processes waiting for the lock which process they are waiting for.
+    if mode == Mode::Exclusive {
+      file.set_len(0)?;
+      file.rewind()?;
+      write!(file, "{}", std::process::id())?;
+    }
+    Ok(Self { file, mode })
+  }
+}
+
+impl Drop for Lock {
+  fn drop(&mut self) {
+    // A process taking the lock shared doesn't record its ID, so the ID of this one mustn't stay behind.
+    if self.mode == Mode::Exclusive {
+      self.file.set_len(0).ok();
+    }
+  }
+}
+
+fn open(path: &Path) -> std::io::Result<File> {
+  if let Some(parent) = path.parent() {
+    std::fs::create_dir_all(parent)?;
+  }
+  File::options()
+    .read(true)
+    .write(true)
+    .create(true)
+    .truncate(false)
+    .open(path)
+}
+
+fn try_lock(file: &File, mode: Mode) -> std::io::Result<bool> {
+  match mode {
+    Mode::Shared => FileExt::try_lock_shared(file),
+    Mode::Exclusive => FileExt::try_lock_exclusive(file),
+  }
+}
+
+/// The ID of the process holding the lock at `path` exclusively, as it recorded it.
+fn holder(path: &Path) -> Option<u32> {
+  std::fs::read_to_string(path).ok()?.trim().parse().ok()
+}
+
+/// The lock of the installation of the version named `name`.
+pub fn version_lock_path(installations_dir: &Path, name: &str) -> PathBuf {
+  installations_dir
+    .join(".locks")
+    .join(format!("{name}.lock"))
+}
+
+/// The lock of the whole store of installations and downloads.
+pub fn store_lock_path(installations_dir: &Path) -> PathBuf {
+  installations_dir.join(".locks").join("store.lock")
+}
+
+/// Locks `version` to install or uninstall it, telling the user if another process is at it.
+pub fn lock_version(version: &Version, config: &PactupConfig) -> std::io::Result<Lock> {
+  let path = version_lock_path(&config.installations_dir(), &version.v_str());
+  Lock::acquire(&path, Mode::Exclusive, |pid| {
+    outln!(
+      config,
+      Info,
+      "Waiting for {} to finish with {}...",
+      describe(pid),
+      format!("Pact {version}").cyan()
+    );
+  })
+}
+
+/// Locks the store, exclusively to remove downloads, or shared to install versions.
+pub fn lock_store(mode: Mode, config: &PactupConfig) -> std::io::Result<Lock> {
+  Lock::acquire(&store_lock_path(&config.installations_dir()), mode, |pid| {
+    outln!(config, Info, "Waiting for {} to finish...", describe(pid));
+  })
+}
+
+fn describe(pid: Option<u32>) -> String {
+  pid.map_or_else(
+    || "another pactup process".to_string(),
+    |pid| format!("pactup (process {pid})"),
+  )
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use pretty_assertions::assert_eq;
+  use std::sync::mpsc;
+  use std::time::Duration;
+
+  #[test]
+  fn test_exclusive_lock() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = version_lock_path(dir.path(), "v5.1.0");
+    let lock = Lock::acquire(&path, Mode::Exclusive, |_| unreachable!()).unwrap();
+    assert!(Lock::try_exclusive(&path).unwrap().is_none());
+
+    let (sender, receiver) = mpsc::channel();
+    std::thread::scope(|scope| {
+      scope.spawn(|| {
+        let _lock = Lock::acquire(&path, Mode::Exclusive, |pid| {
+          sender.send(pid).unwrap();
+        })
+        .unwrap();
+      });
+      assert_eq!(
+        receiver.recv_timeout(Duration::from_secs(10)).unwrap(),
+        Some(std::process::id())
+      );
+      drop(lock);
+    });
+    assert!(Lock::try_exclusive(&path).unwrap().is_some());
+  }
+
+  #[test]
+  fn test_shared_lock() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = store_lock_path(dir.path());
+    let first = Lock::acquire(&path, Mode::Shared, |_| unreachable!()).unwrap();
+    let second = Lock::acquire(&path, Mode::Shared, |_| unreachable!()).unwrap();
+    assert!(Lock::try_exclusive(&path).unwrap().is_none());
+    drop((first, second));
+    assert!(Lock::try_exclusive(&path).unwrap().is_some());
+  }
+}
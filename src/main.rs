@@ -8,24 +8,32 @@
 
 mod alias;
 mod archive;
+mod checksum;
 mod choose_version_for_user_input;
 mod cli;
 mod commands;
 mod config;
 mod current_version;
 mod directory_portal;
+mod download_cache;
 mod downloader;
+mod elf;
 mod fs;
 mod http;
 mod installed_versions;
+mod lock;
 mod package_json;
 mod path_ext;
 mod pretty_serde;
 mod progress;
+mod release_backend;
 mod remote_pact_index;
 mod shell;
+mod signature;
+mod sources;
 mod system_info;
 mod system_version;
+mod transfer;
 mod user_version;
 mod user_version_reader;
 mod version;
@@ -40,5 +48,6 @@ mod directories;
 fn main() {
   env_logger::init();
   let value = crate::cli::parse();
+  http::configure(value.config.http_settings());
   value.subcmd.call(value.config);
 }
@@ -1,12 +1,4 @@
-use std::io::Read;
-
-use indicatif::{ProgressBar, ProgressDrawTarget, ProgressStyle};
-use reqwest::blocking::Response;
-
-pub struct ResponseProgress {
-  progress: Option<ProgressBar>,
-  response: Response,
-}
+use indicatif::{MultiProgress, ProgressBar, ProgressDrawTarget, ProgressStyle};
 
 #[derive(Default, Clone, Debug, clap::ValueEnum)]
 pub enum ProgressConfig {
@@ -28,66 +20,48 @@ impl ProgressConfig {
   }
 }
 
+const TEMPLATE: &str =
+  "{elapsed_precise:.white.dim} {wide_bar:.cyan} {bytes}/{total_bytes} ({bytes_per_sec}, {eta})";
+const PROGRESS_CHARS: &str = "█▉▊▋▌▍▎▏  ";
+
 fn make_progress_bar(size: u64, target: ProgressDrawTarget) -> ProgressBar {
   let bar = ProgressBar::with_draw_target(Some(size), target);
 
   bar.set_style(
-        ProgressStyle::with_template(
-            "{elapsed_precise:.white.dim} {wide_bar:.cyan} {bytes}/{total_bytes} ({bytes_per_sec}, {eta})",
-        )
-        .unwrap()
-        .progress_chars("█▉▊▋▌▍▎▏  "),
-    );
+    ProgressStyle::with_template(TEMPLATE)
+      .unwrap()
+      .progress_chars(PROGRESS_CHARS),
+  );
 
   bar
 }
 
-impl ResponseProgress {
-  pub fn new(response: Response, target: ProgressDrawTarget) -> Self {
-    Self {
-      progress: response
-        .content_length()
-        .map(|len| make_progress_bar(len, target)),
-      response,
-    }
-  }
-
-  pub fn finish(&self) {
-    if let Some(ref bar) = self.progress {
-      bar.finish();
-    }
-  }
-}
-
-impl Read for ResponseProgress {
-  fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
-    let size = self.response.read(buf)?;
-
-    if let Some(ref bar) = self.progress {
-      bar.inc(size as u64);
+/// A progress bar for a download, hidden unless `show` is set. Its length is set once known.
+///
+/// With `bars`, the bar is drawn along with the others in progress, labelled with `name`.
+pub fn download_bar(show: bool, bars: Option<&MultiProgress>, name: &str) -> ProgressBar {
+  match (show, bars) {
+    (false, _) => ProgressBar::hidden(),
+    (true, None) => make_progress_bar(0, ProgressDrawTarget::stderr()),
+    (true, Some(bars)) => {
+      let bar = bars.add(make_progress_bar(0, ProgressDrawTarget::hidden()));
+      bar.set_style(
+        ProgressStyle::with_template(&format!("{{prefix:.cyan}} {TEMPLATE}"))
+          .unwrap()
+          .progress_chars(PROGRESS_CHARS),
+      );
+      bar.set_prefix(name.to_string());
+      bar
     }
-
-    Ok(size)
-  }
-}
-
-impl Drop for ResponseProgress {
-  fn drop(&mut self) {
-    self.finish();
-    eprintln!();
   }
 }
 
 #[cfg(test)]
 mod tests {
   use indicatif::{ProgressDrawTarget, TermLike};
-  use reqwest::blocking::Response;
-  use std::{
-    io::Read,
-    sync::{Arc, Mutex},
-  };
+  use std::sync::{Arc, Mutex};
 
-  use super::ResponseProgress;
+  use super::make_progress_bar;
 
   const CONTENT_LENGTH: usize = 100;
 
@@ -137,29 +111,19 @@ mod tests {
   }
 
   #[test]
-  fn test_reads_data_and_shows_progress() {
-    let response: Response = http::Response::builder()
-      .header("Content-Length", CONTENT_LENGTH)
-      .body("a".repeat(CONTENT_LENGTH))
-      .unwrap()
-      .into();
-
-    let mut buf = [0; CONTENT_LENGTH];
-
+  fn test_shows_progress() {
     let out_buf = Arc::new(Mutex::new(String::new()));
 
-    let mut progress = ResponseProgress::new(
-      response,
+    let progress = make_progress_bar(
+      0,
       ProgressDrawTarget::term_like(Box::new(MockedTerm {
         buf: out_buf.clone(),
       })),
     );
-    let size = progress.read(&mut buf[..]).unwrap();
-
-    drop(progress);
+    progress.set_length(CONTENT_LENGTH as u64);
+    progress.inc(CONTENT_LENGTH as u64);
+    progress.finish();
 
-    assert_eq!(size, CONTENT_LENGTH);
-    assert_eq!(buf, "a".repeat(CONTENT_LENGTH).as_bytes());
     assert!(out_buf.lock().unwrap().contains(&"█".repeat(40)));
   }
 }
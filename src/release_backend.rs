@@ -0,0 +1,27 @@
+use clap::ValueEnum;
+use std::fmt::Display;
+
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq, ValueEnum)]
+pub enum ReleaseBackend {
+  /// Fetch releases through the ungh.sashoush.dev GitHub proxy
+  #[default]
+  Ungh,
+  /// Fetch releases directly from the GitHub REST API
+  #[clap(name = "github")]
+  GitHub,
+}
+
+impl ReleaseBackend {
+  pub fn as_str(self) -> &'static str {
+    match self {
+      ReleaseBackend::Ungh => "ungh",
+      ReleaseBackend::GitHub => "github",
+    }
+  }
+}
+
+impl Display for ReleaseBackend {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    f.write_str(self.as_str())
+  }
+}
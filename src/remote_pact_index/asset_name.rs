@@ -0,0 +1,859 @@
+//! Parses the file names of release assets, such as `pact-4.13.0-ubuntu-x64-22.04.tar.gz`,
+//! to pick the one that fits a platform best.
+//!
+//! A name is made of the `pact-` prefix, the version, then the OS (or distro), the
+//! architecture and the distro version in any order, and the archive extension.
+//! Every part but the version and the OS is optional.
+//!
+//! Sources whose assets are named differently can declare an [`AssetNaming`]: a template
+//! like `pact-cli_{version}_{os}_{arch}`, and synonyms for their OS and architecture names.
+
+use crate::archive::Archive;
+use crate::config::PactupConfig;
+use crate::system_info::{
+  get_platform, DistroVersion, LinuxDistro, Platform, PlatformArch, PlatformOS,
+};
+use crate::version::Version;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt::Display;
+
+/// The glibc shipped by each Ubuntu release, which Linux assets are built on.
+const UBUNTU_GLIBC: &[(DistroVersion, DistroVersion)] = &[
+  (DistroVersion::new(18, 4), DistroVersion::new(2, 27)),
+  (DistroVersion::new(18, 10), DistroVersion::new(2, 28)),
+  (DistroVersion::new(19, 4), DistroVersion::new(2, 29)),
+  (DistroVersion::new(19, 10), DistroVersion::new(2, 30)),
+  (DistroVersion::new(20, 4), DistroVersion::new(2, 31)),
+  (DistroVersion::new(20, 10), DistroVersion::new(2, 32)),
+  (DistroVersion::new(21, 4), DistroVersion::new(2, 33)),
+  (DistroVersion::new(21, 10), DistroVersion::new(2, 34)),
+  (DistroVersion::new(22, 4), DistroVersion::new(2, 35)),
+  (DistroVersion::new(22, 10), DistroVersion::new(2, 36)),
+  (DistroVersion::new(23, 4), DistroVersion::new(2, 37)),
+  (DistroVersion::new(23, 10), DistroVersion::new(2, 38)),
+  (DistroVersion::new(24, 4), DistroVersion::new(2, 39)),
+  (DistroVersion::new(24, 10), DistroVersion::new(2, 40)),
+  (DistroVersion::new(25, 4), DistroVersion::new(2, 41)),
+];
+
+/// The glibc an asset built on the given Ubuntu release requires, if known.
+fn required_glibc(ubuntu: DistroVersion) -> Option<DistroVersion> {
+  UBUNTU_GLIBC
+    .iter()
+    .find(|(version, _)| *version == ubuntu)
+    .map(|(_, glibc)| *glibc)
+}
+
+/// The system an asset is picked for.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AssetTarget {
+  pub platform: Platform,
+  /// The Linux distro to check the asset variants against, if known.
+  pub distro: Option<LinuxDistro>,
+  /// Only accept the assets built on this distro version, bypassing the compatibility checks.
+  pub variant: Option<DistroVersion>,
+}
+
+impl AssetTarget {
+  /// A target that only knows about the platform, so every distro variant is considered compatible.
+  #[cfg(test)]
+  pub fn new(platform: Platform) -> Self {
+    Self {
+      platform,
+      distro: None,
+      variant: None,
+    }
+  }
+
+  /// The system pactup runs on, honoring `--arch`, and detecting the Linux distro when relevant.
+  pub fn current(config: &PactupConfig) -> Self {
+    let Platform(os, _) = get_platform();
+    let platform = Platform(os, config.arch);
+    let distro =
+      (platform.0 == PlatformOS::Linux && config.linux_variant.is_none()).then(LinuxDistro::detect);
+    Self {
+      platform,
+      distro,
+      variant: config.linux_variant,
+    }
+  }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AssetName {
+  /// The version as written in the name, which may be partial, like `4.13`.
+  pub version: String,
+  pub os: PlatformOS,
+  /// The architecture, when the name specifies one. Assets without one are built for x64.
+  pub arch: Option<PlatformArch>,
+  pub distro: Option<String>,
+  pub distro_version: Option<DistroVersion>,
+  /// How the binary is linked, when the name says so. Other Linux assets are linked against glibc.
+  pub linkage: Option<Linkage>,
+  pub format: Archive,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Linkage {
+  /// Dynamically linked against musl, for Alpine and the like.
+  Musl,
+  /// Statically linked, so it runs with any libc.
+  Static,
+}
+
+impl Linkage {
+  pub fn as_str(self) -> &'static str {
+    match self {
+      Self::Musl => "musl",
+      Self::Static => "static",
+    }
+  }
+}
+
+/// Why an asset can't be installed on a platform.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Rejection {
+  UnrecognizedName,
+  TemplateMismatch {
+    template: String,
+  },
+  VersionMismatch {
+    found: String,
+  },
+  OsMismatch {
+    found: PlatformOS,
+  },
+  ArchMismatch {
+    found: PlatformArch,
+  },
+  MissingArch,
+  GlibcOnly,
+  MuslOnly,
+  OtherVariant {
+    found: DistroVersion,
+  },
+  GlibcTooOld {
+    required: DistroVersion,
+    found: DistroVersion,
+  },
+  DistroTooOld {
+    required: DistroVersion,
+    found: DistroVersion,
+  },
+}
+
+impl Display for Rejection {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    match self {
+      Self::UnrecognizedName => f.write_str("not a recognized Pact asset name"),
+      Self::TemplateMismatch { template } => write!(f, "doesn't match the template {template}"),
+      Self::VersionMismatch { found } => write!(f, "built for another version ({found})"),
+      Self::OsMismatch { found } => write!(f, "built for another OS ({found})"),
+      Self::ArchMismatch { found } => write!(f, "built for another architecture ({found})"),
+      Self::MissingArch => f.write_str("no architecture in the name, assumed to be x64"),
+      Self::GlibcOnly => f.write_str("linked against glibc, which musl systems can't run"),
+      Self::MuslOnly => f.write_str("linked against musl, which the system doesn't use"),
+      Self::OtherVariant { found } => write!(f, "not the configured Linux variant ({found})"),
+      Self::GlibcTooOld { required, found } => {
+        write!(f, "requires glibc {required}, but the system has {found}")
+      }
+      Self::DistroTooOld { required, found } => {
+        write!(
+          f,
+          "built on Ubuntu {required}, newer than the system's {found}"
+        )
+      }
+    }
+  }
+}
+
+fn parse_os(token: &str) -> Option<(PlatformOS, Option<&str>)> {
+  match token {
+    "linux" => Some((PlatformOS::Linux, None)),
+    "ubuntu" => Some((PlatformOS::Linux, Some(token))),
+    "darwin" | "osx" | "macos" => Some((PlatformOS::MacOS, None)),
+    "windows" | "win" => Some((PlatformOS::Windows, None)),
+    _ => None,
+  }
+}
+
+fn parse_arch(token: &str) -> Option<PlatformArch> {
+  match token {
+    "x64" | "x86_64" | "amd64" => Some(PlatformArch::X64),
+    "arm64" | "aarch64" => Some(PlatformArch::Arm64),
+    "armv7l" | "arm" => Some(PlatformArch::Armv7l),
+    "x86" | "i386" => Some(PlatformArch::X86),
+    "ppc64le" => Some(PlatformArch::Ppc64le),
+    "ppc64" => Some(PlatformArch::Ppc64),
+    "s390x" => Some(PlatformArch::S390x),
+    _ => None,
+  }
+}
+
+fn parse_linkage(token: &str) -> Option<Linkage> {
+  match token {
+    "musl" => Some(Linkage::Musl),
+    "static" => Some(Linkage::Static),
+    _ => None,
+  }
+}
+
+fn split_format(file_name: &str) -> Option<(&str, Archive)> {
+  [
+    (".tar.gz", Archive::TarGz),
+    (".tar.xz", Archive::TarXz),
+    (".tgz", Archive::TarGz),
+    (".tar.zst", Archive::TarZst),
+    (".tzst", Archive::TarZst),
+    (".tar.bz2", Archive::TarBz2),
+    (".tbz2", Archive::TarBz2),
+    (".zip", Archive::Zip),
+  ]
+  .into_iter()
+  .find_map(|(extension, format)| file_name.strip_suffix(extension).map(|stem| (stem, format)))
+}
+
+/// A part of an asset name, as written in templates.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Field {
+  Version,
+  Os,
+  Arch,
+  DistroVersion,
+  Linkage,
+}
+
+impl Field {
+  fn parse(placeholder: &str) -> Option<Self> {
+    match placeholder {
+      "version" => Some(Self::Version),
+      "os" => Some(Self::Os),
+      "arch" => Some(Self::Arch),
+      "distro_version" => Some(Self::DistroVersion),
+      "linkage" => Some(Self::Linkage),
+      _ => None,
+    }
+  }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Segment<'a> {
+  Literal(&'a str),
+  Field(Field),
+}
+
+/// Splits a template into literal text and `{field}` placeholders.
+fn segments(template: &str) -> Result<Vec<Segment<'_>>, NamingError> {
+  let mut segments = vec![];
+  let mut rest = template;
+  while let Some(start) = rest.find('{') {
+    if start > 0 {
+      segments.push(Segment::Literal(&rest[..start]));
+    }
+    let end = rest[start..]
+      .find('}')
+      .ok_or(NamingError::UnclosedPlaceholder)?
+      + start;
+    let placeholder = &rest[start + 1..end];
+    let field = Field::parse(placeholder)
+      .ok_or_else(|| NamingError::UnknownPlaceholder(placeholder.to_string()))?;
+    if segments.contains(&Segment::Field(field)) {
+      return Err(NamingError::DuplicatePlaceholder(placeholder.to_string()));
+    }
+    segments.push(Segment::Field(field));
+    rest = &rest[end + 1..];
+  }
+  if !rest.is_empty() {
+    segments.push(Segment::Literal(rest));
+  }
+  if !segments.contains(&Segment::Field(Field::Version))
+    || !segments.contains(&Segment::Field(Field::Os))
+  {
+    return Err(NamingError::MissingPlaceholder);
+  }
+  Ok(segments)
+}
+
+/// How a source names its release assets.
+///
+/// By default, names follow the scheme of the Pact releases, described in the module documentation.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AssetNaming {
+  /// A template like `pact-cli_{version}_{os}_{arch}`, matched against the file name without its
+  /// archive extension. The placeholders are `{version}`, `{os}`, `{arch}`, `{distro_version}` and `{linkage}`.
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub template: Option<String>,
+  /// OS names used by the assets, mapped to names pactup knows, e.g. `mac` to `darwin`.
+  #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+  pub os_names: BTreeMap<String, String>,
+  /// Architecture names used by the assets, mapped to names pactup knows, e.g. `x86-64` to `x64`.
+  #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+  pub arch_names: BTreeMap<String, String>,
+}
+
+impl AssetNaming {
+  pub fn is_default(&self) -> bool {
+    self == &Self::default()
+  }
+
+  /// Checks the template and makes sure the synonyms map to names pactup knows.
+  pub fn validate(&self) -> Result<(), NamingError> {
+    if let Some(template) = &self.template {
+      segments(template)?;
+    }
+    if let Some(name) = self.os_names.values().find(|x| parse_os(x).is_none()) {
+      return Err(NamingError::UnknownOs(name.clone()));
+    }
+    if let Some(name) = self.arch_names.values().find(|x| parse_arch(x).is_none()) {
+      return Err(NamingError::UnknownArch(name.clone()));
+    }
+    Ok(())
+  }
+
+  fn os<'a>(&'a self, token: &'a str) -> Option<(PlatformOS, Option<&'a str>)> {
+    parse_os(self.os_names.get(token).map_or(token, String::as_str))
+  }
+
+  fn arch(&self, token: &str) -> Option<PlatformArch> {
+    parse_arch(self.arch_names.get(token).map_or(token, String::as_str))
+  }
+
+  /// Parses an asset file name, or explains why it doesn't follow this naming.
+  pub fn parse(&self, file_name: &str) -> Result<AssetName, Rejection> {
+    match &self.template {
+      None => self
+        .parse_default(file_name)
+        .ok_or(Rejection::UnrecognizedName),
+      Some(template) => {
+        self
+          .parse_template(template, file_name)
+          .ok_or_else(|| Rejection::TemplateMismatch {
+            template: template.clone(),
+          })
+      }
+    }
+  }
+
+  fn parse_default(&self, file_name: &str) -> Option<AssetName> {
+    let (stem, format) = split_format(file_name)?;
+    let tokens: Vec<&str> = stem.strip_prefix("pact-")?.split('-').collect();
+
+    // The version runs until the first platform token, and may contain dashes itself.
+    let version_len = tokens
+      .iter()
+      .position(|token| self.os(token).is_some() || self.arch(token).is_some())?;
+    if version_len == 0 {
+      return None;
+    }
+
+    let mut os = None;
+    let mut distro = None;
+    let mut arch = None;
+    let mut distro_version = None;
+    let mut linkage = None;
+    for token in &tokens[version_len..] {
+      if let Some((token_os, token_distro)) = self.os(token).filter(|_| os.is_none()) {
+        os = Some(token_os);
+        distro = token_distro.map(String::from);
+      } else if let Some(token_arch) = self.arch(token).filter(|_| arch.is_none()) {
+        arch = Some(token_arch);
+      } else if let Some(token_linkage) = parse_linkage(token).filter(|_| linkage.is_none()) {
+        linkage = Some(token_linkage);
+      } else if let Some(version) = token
+        .parse::<DistroVersion>()
+        .ok()
+        .filter(|_| distro_version.is_none())
+      {
+        distro_version = Some(version);
+      } else {
+        return None;
+      }
+    }
+
+    Some(AssetName {
+      version: tokens[..version_len].join("-"),
+      os: os?,
+      arch,
+      distro,
+      distro_version,
+      linkage,
+      format,
+    })
+  }
+
+  fn parse_template(&self, template: &str, file_name: &str) -> Option<AssetName> {
+    let (stem, format) = split_format(file_name)?;
+    let segments = segments(template).ok()?;
+    let mut values = vec![];
+    if !self.match_segments(&segments, stem, &mut values) {
+      return None;
+    }
+
+    let mut name = AssetName {
+      version: String::new(),
+      os: PlatformOS::Linux,
+      arch: None,
+      distro: None,
+      distro_version: None,
+      linkage: None,
+      format,
+    };
+    for (field, value) in values {
+      match field {
+        Field::Version => name.version = value.to_string(),
+        Field::Os => {
+          let (os, distro) = self.os(value)?;
+          name.os = os;
+          name.distro = distro.map(String::from);
+        }
+        Field::Arch => name.arch = self.arch(value),
+        Field::DistroVersion => name.distro_version = value.parse().ok(),
+        Field::Linkage => name.linkage = parse_linkage(value),
+      }
+    }
+    Some(name)
+  }
+
+  /// Matches `input` against `segments`, collecting the value of each field.
+  ///
+  /// Fields take the shortest value that lets the rest of the name match, and must hold
+  /// a value that makes sense for them, so `{version}_{os}` splits `5.0.0_rc1_linux` right.
+  fn match_segments<'a>(
+    &self,
+    segments: &[Segment<'_>],
+    input: &'a str,
+    values: &mut Vec<(Field, &'a str)>,
+  ) -> bool {
+    let Some((segment, rest)) = segments.split_first() else {
+      return input.is_empty();
+    };
+    let field = match segment {
+      Segment::Literal(literal) => {
+        return input
+          .strip_prefix(literal)
+          .is_some_and(|input| self.match_segments(rest, input, values));
+      }
+      Segment::Field(field) => *field,
+    };
+    let ends: Vec<usize> = if rest.is_empty() {
+      vec![input.len()]
+    } else {
+      input
+        .char_indices()
+        .skip(1)
+        .map(|(index, _)| index)
+        .chain([input.len()])
+        .collect()
+    };
+    for end in ends {
+      let value = &input[..end];
+      let valid = match field {
+        Field::Version => true,
+        Field::Os => self.os(value).is_some(),
+        Field::Arch => self.arch(value).is_some(),
+        Field::DistroVersion => value.parse::<DistroVersion>().is_ok(),
+        Field::Linkage => parse_linkage(value).is_some(),
+      };
+      if !valid {
+        continue;
+      }
+      values.push((field, value));
+      if self.match_segments(rest, &input[end..], values) {
+        return true;
+      }
+      values.pop();
+    }
+    false
+  }
+}
+
+impl Display for AssetNaming {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    match &self.template {
+      Some(template) => write!(f, "template {template}")?,
+      None => f.write_str("the default Pact naming")?,
+    }
+    let synonyms: Vec<String> = self
+      .os_names
+      .iter()
+      .chain(&self.arch_names)
+      .map(|(name, known)| format!("{name}={known}"))
+      .collect();
+    if !synonyms.is_empty() {
+      write!(f, " ({})", synonyms.join(", "))?;
+    }
+    Ok(())
+  }
+}
+
+/// Why an [`AssetNaming`] can't be used.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum NamingError {
+  #[error("unclosed placeholder in the template")]
+  UnclosedPlaceholder,
+  #[error("unknown placeholder {{{0}}} in the template")]
+  UnknownPlaceholder(String),
+  #[error("the placeholder {{{0}}} appears twice in the template")]
+  DuplicatePlaceholder(String),
+  #[error("the template must contain {{version}} and {{os}}")]
+  MissingPlaceholder,
+  #[error("{0:?} is not an OS name pactup knows")]
+  UnknownOs(String),
+  #[error("{0:?} is not an architecture name pactup knows")]
+  UnknownArch(String),
+}
+
+impl AssetName {
+  /// Parses an asset file name following the default naming.
+  #[cfg(test)]
+  pub fn parse(file_name: &str) -> Option<Self> {
+    AssetNaming::default().parse(file_name).ok()
+  }
+
+  /// Whether the version in the name refers to `tag`. Partial versions like `4.13` match `4.13.0`.
+  pub fn matches_version(&self, tag: &Version) -> bool {
+    match tag {
+      Version::Semver(semver) => {
+        let expected = [semver.major, semver.minor, semver.patch];
+        let parts: Vec<&str> = self.version.split('.').collect();
+        parts.len() <= expected.len()
+          && parts
+            .iter()
+            .zip(expected)
+            .all(|(part, expected)| part.parse::<u64>().ok() == Some(expected))
+      }
+      Version::Nightly(tag) | Version::Alias(tag) => &self.version == tag,
+      Version::Latest => self.version == "latest",
+      Version::Bypassed => self.version == "system",
+    }
+  }
+
+  /// Checks whether this asset can be installed for `tag` on `target`.
+  pub fn check(&self, tag: &Version, target: &AssetTarget) -> Result<(), Rejection> {
+    let Platform(os, arch) = &target.platform;
+    // musl hosts run x64 binaries too, as long as they don't need glibc.
+    let musl_host = *arch == PlatformArch::X64Musl;
+    let arch = if musl_host { &PlatformArch::X64 } else { arch };
+    if !self.matches_version(tag) {
+      return Err(Rejection::VersionMismatch {
+        found: self.version.clone(),
+      });
+    }
+    if self.os != *os {
+      return Err(Rejection::OsMismatch { found: self.os });
+    }
+    match self.arch {
+      Some(found) if found != *arch => return Err(Rejection::ArchMismatch { found }),
+      None if *arch != PlatformArch::X64 => return Err(Rejection::MissingArch),
+      _ => {}
+    }
+    if self.os == PlatformOS::Linux {
+      match (musl_host, self.linkage) {
+        (true, None) => return Err(Rejection::GlibcOnly),
+        (false, Some(Linkage::Musl)) => return Err(Rejection::MuslOnly),
+        _ => {}
+      }
+    }
+    match self.distro_version {
+      Some(variant) if self.os == PlatformOS::Linux && self.linkage.is_none() => {
+        Self::check_variant(variant, target)
+      }
+      _ => Ok(()),
+    }
+  }
+
+  /// Checks a Linux asset built on the `variant` Ubuntu release against the distro of `target`.
+  fn check_variant(variant: DistroVersion, target: &AssetTarget) -> Result<(), Rejection> {
+    if let Some(forced) = target.variant {
+      return if variant == forced {
+        Ok(())
+      } else {
+        Err(Rejection::OtherVariant { found: variant })
+      };
+    }
+    let Some(distro) = &target.distro else {
+      return Ok(());
+    };
+    if let (Some(required), Some(found)) = (required_glibc(variant), distro.glibc) {
+      if found < required {
+        return Err(Rejection::GlibcTooOld { required, found });
+      }
+    }
+    if let (Some("ubuntu"), Some(found)) = (distro.id.as_deref(), distro.version) {
+      if found < variant {
+        return Err(Rejection::DistroTooOld {
+          required: variant,
+          found,
+        });
+      }
+    }
+    Ok(())
+  }
+
+  /// How well this asset fits, among the assets that passed [`AssetName::check`]. Higher is better.
+  ///
+  /// Assets naming the architecture explicitly come first, then the newest distro
+  /// version (incompatible ones were rejected already), and tarballs are preferred over zip files.
+  pub fn rank(&self) -> impl Ord {
+    let format_rank = match self.format {
+      Archive::TarXz | Archive::TarGz | Archive::TarZst | Archive::TarBz2 => 1,
+      Archive::Zip => 0,
+    };
+    (self.arch.is_some(), self.distro_version, format_rank)
+  }
+
+  /// The Linux variant the asset was built for, such as `22.04` or `musl`, when the name tells one.
+  pub fn variant(&self) -> Option<String> {
+    let parts: Vec<String> = self
+      .distro_version
+      .map(|distro_version| distro_version.to_string())
+      .into_iter()
+      .chain(self.linkage.map(|linkage| linkage.as_str().to_string()))
+      .collect();
+    Some(parts.join(" ")).filter(|variant| !variant.is_empty())
+  }
+}
+
+impl Display for AssetName {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    write!(
+      f,
+      "{} {}",
+      self.version,
+      self.distro.as_deref().unwrap_or(self.os.as_str())
+    )?;
+    if let Some(distro_version) = self.distro_version {
+      write!(f, " {distro_version}")?;
+    }
+    let arch = self.arch.map_or("x64 (implied)", PlatformArch::as_str);
+    write!(f, ", {arch}")?;
+    if let Some(linkage) = self.linkage {
+      write!(f, " {}", linkage.as_str())?;
+    }
+    write!(f, ", {}", self.format.as_str())
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use pretty_assertions::assert_eq;
+
+  #[test]
+  fn test_parse() {
+    assert_eq!(
+      AssetName::parse("pact-4.13.0-ubuntu-x64-22.10.tar.gz"),
+      Some(AssetName {
+        version: "4.13.0".to_string(),
+        os: PlatformOS::Linux,
+        arch: Some(PlatformArch::X64),
+        distro: Some("ubuntu".to_string()),
+        distro_version: Some(DistroVersion::new(22, 10)),
+        linkage: None,
+        format: Archive::TarGz,
+      })
+    );
+    assert_eq!(
+      AssetName::parse("pact-5.0.0-rc1-aarch64-darwin.zip"),
+      Some(AssetName {
+        version: "5.0.0-rc1".to_string(),
+        os: PlatformOS::MacOS,
+        arch: Some(PlatformArch::Arm64),
+        distro: None,
+        distro_version: None,
+        linkage: None,
+        format: Archive::Zip,
+      })
+    );
+  }
+
+  #[test]
+  fn test_parse_rejects_unknown_names() {
+    assert_eq!(AssetName::parse("pact-4.13.0-linux-invalid.tar.gz"), None);
+    assert_eq!(AssetName::parse("pact-4.13.0-linux-x64.deb"), None);
+    assert_eq!(AssetName::parse("pact-4.13.0-x64.tar.gz"), None);
+    assert_eq!(AssetName::parse("pact-linux-x64.tar.gz"), None);
+    assert_eq!(AssetName::parse("chainweb-4.13.0-linux.tar.gz"), None);
+  }
+
+  #[test]
+  fn test_parse_template() {
+    let naming = AssetNaming {
+      template: Some("pact-cli_{version}_{os}_{arch}".to_string()),
+      os_names: BTreeMap::new(),
+      arch_names: BTreeMap::from([("x86-64".to_string(), "x64".to_string())]),
+    };
+    assert_eq!(
+      naming.parse("pact-cli_5.0.0_linux_amd64.tgz"),
+      Ok(AssetName {
+        version: "5.0.0".to_string(),
+        os: PlatformOS::Linux,
+        arch: Some(PlatformArch::X64),
+        distro: None,
+        distro_version: None,
+        linkage: None,
+        format: Archive::TarGz,
+      })
+    );
+    let name = naming
+      .parse("pact-cli_5.0.0_rc1_darwin_x86-64.zip")
+      .unwrap();
+    assert_eq!(name.version, "5.0.0_rc1");
+    assert_eq!(name.os, PlatformOS::MacOS);
+    assert_eq!(name.arch, Some(PlatformArch::X64));
+    assert_eq!(
+      naming.parse("pact-5.0.0-linux-x64.tar.gz"),
+      Err(Rejection::TemplateMismatch {
+        template: "pact-cli_{version}_{os}_{arch}".to_string()
+      })
+    );
+  }
+
+  #[test]
+  fn test_default_naming_with_synonyms() {
+    let naming = AssetNaming {
+      os_names: BTreeMap::from([("mac".to_string(), "darwin".to_string())]),
+      ..AssetNaming::default()
+    };
+    let name = naming.parse("pact-5.0.0-mac-arm64.tar.gz").unwrap();
+    assert_eq!(name.os, PlatformOS::MacOS);
+    assert_eq!(
+      AssetNaming::default().parse("pact-5.0.0-mac-arm64.tar.gz"),
+      Err(Rejection::UnrecognizedName)
+    );
+  }
+
+  #[test]
+  fn test_validate_naming() {
+    let template = |template: &str| AssetNaming {
+      template: Some(template.to_string()),
+      ..AssetNaming::default()
+    };
+    assert_eq!(template("pact_{version}_{os}").validate(), Ok(()));
+    assert_eq!(
+      template("pact_{version}_{os").validate(),
+      Err(NamingError::UnclosedPlaceholder)
+    );
+    assert_eq!(
+      template("pact_{version}_{platform}").validate(),
+      Err(NamingError::UnknownPlaceholder("platform".to_string()))
+    );
+    assert_eq!(
+      template("pact_{version}_{os}_{os}").validate(),
+      Err(NamingError::DuplicatePlaceholder("os".to_string()))
+    );
+    let naming = AssetNaming {
+      arch_names: BTreeMap::from([("amd64".to_string(), "x86-64".to_string())]),
+      ..AssetNaming::default()
+    };
+    assert_eq!(
+      naming.validate(),
+      Err(NamingError::UnknownArch("x86-64".to_string()))
+    );
+  }
+
+  #[test]
+  fn test_check_missing_arch() {
+    let name = AssetName::parse("pact-4.13.0-linux-20.04.zip").unwrap();
+    let tag = Version::parse("4.13.0").unwrap();
+    let target = |arch| AssetTarget::new(Platform(PlatformOS::Linux, arch));
+    assert_eq!(name.check(&tag, &target(PlatformArch::X64)), Ok(()));
+    assert_eq!(
+      name.check(&tag, &target(PlatformArch::Arm64)),
+      Err(Rejection::MissingArch)
+    );
+  }
+
+  fn linux_target(os_release: &str, glibc: Option<DistroVersion>) -> AssetTarget {
+    AssetTarget {
+      platform: Platform(PlatformOS::Linux, PlatformArch::X64),
+      distro: Some(LinuxDistro::from_os_release(os_release, glibc)),
+      variant: None,
+    }
+  }
+
+  #[test]
+  fn test_check_variant_against_glibc() {
+    let name = AssetName::parse("pact-4.13.0-linux-22.04.zip").unwrap();
+    let tag = Version::parse("4.13.0").unwrap();
+
+    let debian_11 = linux_target(
+      "ID=debian\nVERSION_ID=\"11\"",
+      Some(DistroVersion::new(2, 31)),
+    );
+    assert_eq!(
+      name.check(&tag, &debian_11),
+      Err(Rejection::GlibcTooOld {
+        required: DistroVersion::new(2, 35),
+        found: DistroVersion::new(2, 31),
+      })
+    );
+
+    let fedora = linux_target("ID=fedora\nVERSION_ID=40", Some(DistroVersion::new(2, 39)));
+    assert_eq!(name.check(&tag, &fedora), Ok(()));
+  }
+
+  #[test]
+  fn test_check_variant_against_ubuntu() {
+    let name = AssetName::parse("pact-4.13.0-linux-22.04.zip").unwrap();
+    let tag = Version::parse("4.13.0").unwrap();
+
+    let ubuntu_20_04 = linux_target("ID=ubuntu\nVERSION_ID=\"20.04\"", None);
+    assert_eq!(
+      name.check(&tag, &ubuntu_20_04),
+      Err(Rejection::DistroTooOld {
+        required: DistroVersion::new(22, 4),
+        found: DistroVersion::new(20, 4),
+      })
+    );
+
+    let ubuntu_24_04 = linux_target("ID=ubuntu\nVERSION_ID=\"24.04\"", None);
+    assert_eq!(name.check(&tag, &ubuntu_24_04), Ok(()));
+  }
+
+  #[test]
+  fn test_check_forced_variant() {
+    let tag = Version::parse("4.13.0").unwrap();
+    let mut target = linux_target("ID=ubuntu\nVERSION_ID=\"18.04\"", None);
+    target.variant = Some(DistroVersion::new(22, 4));
+
+    let name = AssetName::parse("pact-4.13.0-linux-22.04.zip").unwrap();
+    assert_eq!(name.check(&tag, &target), Ok(()));
+    let name = AssetName::parse("pact-4.13.0-linux-20.04.zip").unwrap();
+    assert_eq!(
+      name.check(&tag, &target),
+      Err(Rejection::OtherVariant {
+        found: DistroVersion::new(20, 4)
+      })
+    );
+  }
+
+  #[test]
+  fn test_check_musl_host() {
+    let tag = Version::parse("4.13.0").unwrap();
+    let musl = AssetTarget::new(Platform(PlatformOS::Linux, PlatformArch::X64Musl));
+    let glibc = AssetTarget::new(Platform(PlatformOS::Linux, PlatformArch::X64));
+
+    let name = AssetName::parse("pact-4.13.0-linux-x64-musl.tar.gz").unwrap();
+    assert_eq!(name.linkage, Some(Linkage::Musl));
+    assert_eq!(name.check(&tag, &musl), Ok(()));
+    assert_eq!(name.check(&tag, &glibc), Err(Rejection::MuslOnly));
+
+    let name = AssetName::parse("pact-4.13.0-linux-static.tar.gz").unwrap();
+    assert_eq!(name.check(&tag, &musl), Ok(()));
+    assert_eq!(name.check(&tag, &glibc), Ok(()));
+
+    let name = AssetName::parse("pact-4.13.0-linux-22.04.zip").unwrap();
+    assert_eq!(name.check(&tag, &musl), Err(Rejection::GlibcOnly));
+  }
+
+  #[test]
+  fn test_rank() {
+    let rank = |name: &str| AssetName::parse(name).unwrap().rank();
+    assert!(rank("pact-4.13.0-linux-x64.zip") > rank("pact-4.13.0-linux-22.04.tar.gz"));
+    assert!(rank("pact-4.13.0-linux-22.04.zip") > rank("pact-4.13.0-linux-20.04.zip"));
+    assert!(rank("pact-4.13.0-linux-22.04.tar.gz") > rank("pact-4.13.0-linux-22.04.zip"));
+  }
+}
@@ -0,0 +1,139 @@
+//! An on-disk cache of the releases listed by each [`ReleaseProvider`](super::ReleaseProvider).
+//!
+//! Every provider gets its own JSON file, keyed by [`ReleaseProvider::cache_key`](super::ReleaseProvider::cache_key),
+//! holding the releases, the time they were fetched at and the `ETag` to revalidate them with.
+
+use super::Release;
+use chrono::{DateTime, Utc};
+use log::{debug, warn};
+use serde::{Deserialize, Serialize};
+use std::io::Write;
+use std::path::PathBuf;
+use std::time::Duration;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CachedIndex {
+  pub fetched_at: DateTime<Utc>,
+  pub etag: Option<String>,
+  pub releases: Vec<Release>,
+}
+
+impl CachedIndex {
+  pub fn new(releases: Vec<Release>, etag: Option<String>) -> Self {
+    Self {
+      fetched_at: Utc::now(),
+      etag,
+      releases,
+    }
+  }
+
+  pub fn is_fresh(&self, ttl: Duration) -> bool {
+    let age = Utc::now().signed_duration_since(self.fetched_at);
+    age.to_std().is_ok_and(|age| age < ttl)
+  }
+}
+
+pub struct IndexCache {
+  dir: PathBuf,
+}
+
+impl IndexCache {
+  pub fn new(dir: PathBuf) -> Self {
+    Self { dir }
+  }
+
+  fn path_for(&self, key: &str) -> PathBuf {
+    let file_name: String = key
+      .chars()
+      .map(|c| {
+        if c.is_ascii_alphanumeric() || c == '-' {
+          c
+        } else {
+          '_'
+        }
+      })
+      .collect();
+    self.dir.join(format!("{file_name}.json"))
+  }
+
+  /// Reads the cached releases for `key`. A missing or unreadable entry is treated as a cache miss.
+  pub fn read(&self, key: &str) -> Option<CachedIndex> {
+    let path = self.path_for(key);
+    let contents = std::fs::read_to_string(&path).ok()?;
+    match serde_json::from_str(&contents) {
+      Ok(cached) => Some(cached),
+      Err(err) => {
+        debug!("Ignoring the corrupted release cache {:?}: {}", path, err);
+        None
+      }
+    }
+  }
+
+  /// Stores the releases for `key`. Failing to write the cache is not fatal, only logged.
+  pub fn write(&self, key: &str, cached: &CachedIndex) {
+    if let Err(err) = self.try_write(key, cached) {
+      warn!("Can't write the release cache for {}: {}", key, err);
+    }
+  }
+
+  fn try_write(&self, key: &str, cached: &CachedIndex) -> std::io::Result<()> {
+    std::fs::create_dir_all(&self.dir)?;
+    // Write to a temporary file first, so concurrent readers never see a partial file.
+    let mut file = tempfile::NamedTempFile::new_in(&self.dir)?;
+    file.write_all(&serde_json::to_vec(cached)?)?;
+    file.persist(self.path_for(key)).map_err(|err| err.error)?;
+    Ok(())
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::version::Version;
+  use pretty_assertions::assert_eq;
+
+  #[test]
+  fn test_roundtrip() {
+    let dir = tempfile::tempdir().unwrap();
+    let cache = IndexCache::new(dir.path().to_path_buf());
+    assert_eq!(cache.read("ungh:kadena-io/pact"), None);
+
+    let cached = CachedIndex::new(
+      vec![Release {
+        tag: Version::parse("4.13.0").unwrap(),
+        name: None,
+        draft: false,
+        prerelease: false,
+        created_at: None,
+        published_at: None,
+        markdown: None,
+        assets: vec![],
+        source: None,
+      }],
+      Some("\"abc\"".to_string()),
+    );
+    cache.write("ungh:kadena-io/pact", &cached);
+
+    assert_eq!(cache.read("ungh:kadena-io/pact"), Some(cached));
+    assert!(dir.path().join("ungh_kadena-io_pact.json").exists());
+  }
+
+  #[test]
+  fn test_freshness() {
+    let mut cached = CachedIndex::new(vec![], None);
+    assert!(cached.is_fresh(Duration::from_secs(60)));
+    assert!(!cached.is_fresh(Duration::ZERO));
+
+    cached.fetched_at -= chrono::Duration::minutes(2);
+    assert!(!cached.is_fresh(Duration::from_secs(60)));
+  }
+
+  #[test]
+  fn test_corrupted_entry_is_a_miss() {
+    let dir = tempfile::tempdir().unwrap();
+    let cache = IndexCache::new(dir.path().to_path_buf());
+    std::fs::write(dir.path().join("broken.json"), "{").unwrap();
+    assert_eq!(cache.read("broken"), None);
+  }
+}
@@ -0,0 +1,288 @@
+//! Fetches releases directly from the GitHub REST API.
+//!
+//! Works against github.com as well as GitHub Enterprise hosts, by pointing
+//! `--github-api-url` to the instance's API root (e.g. `https://github.example.com/api/v3`).
+
+use super::{etag_of, get_if_modified, Asset, Error, Fetch, Release, ReleaseProvider};
+use crate::http::header::{self, HeaderMap, HeaderValue};
+use crate::pretty_serde::DecodeError;
+use crate::version::Version;
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use url::Url;
+
+/// The maximum page size GitHub allows for the releases endpoint.
+const PER_PAGE: &str = "100";
+
+#[derive(Debug, Deserialize)]
+struct GitHubAsset {
+  content_type: Option<String>,
+  size: Option<u64>,
+  created_at: Option<DateTime<Utc>>,
+  updated_at: Option<DateTime<Utc>>,
+  download_count: Option<u64>,
+  browser_download_url: Url,
+  /// The URL of the asset in the API, e.g. `https://api.github.com/repos/{repo}/releases/assets/{id}`.
+  url: Option<Url>,
+  /// The digest of the asset, e.g. `sha256:2cf2…`. Only set for assets uploaded since mid 2025.
+  digest: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct GitHubRelease {
+  tag_name: Version,
+  name: Option<String>,
+  draft: bool,
+  prerelease: bool,
+  created_at: Option<DateTime<Utc>>,
+  published_at: Option<DateTime<Utc>>,
+  body: Option<String>,
+  assets: Vec<GitHubAsset>,
+}
+
+impl From<GitHubRelease> for Release {
+  fn from(release: GitHubRelease) -> Self {
+    Self {
+      tag: release.tag_name,
+      name: release.name,
+      draft: release.draft,
+      prerelease: release.prerelease,
+      created_at: release.created_at,
+      published_at: release.published_at,
+      markdown: release.body,
+      assets: release
+        .assets
+        .into_iter()
+        .map(|asset| Asset {
+          content_type: asset.content_type,
+          size: asset.size,
+          created_at: asset.created_at,
+          updated_at: asset.updated_at,
+          download_count: asset.download_count,
+          download_url: asset.browser_download_url,
+          api_url: asset.url,
+          sha256: asset.digest.and_then(|digest| digest.parse().ok()),
+        })
+        .collect(),
+      source: None,
+    }
+  }
+}
+
+/// A single page of the releases endpoint.
+struct Page {
+  text: String,
+  etag: Option<String>,
+  next: Option<String>,
+}
+
+#[derive(Debug)]
+pub struct GitHub {
+  api_url: Url,
+  token: Option<String>,
+  repo: String,
+}
+
+impl GitHub {
+  pub fn new(api_url: Url, token: Option<String>, repo: impl Into<String>) -> Self {
+    Self {
+      api_url,
+      token,
+      repo: repo.into(),
+    }
+  }
+
+  fn repo_url(&self, path: &str) -> String {
+    format!(
+      "{}/repos/{}/{}",
+      self.api_url.as_str().trim_end_matches('/'),
+      self.repo.trim_matches('/'),
+      path
+    )
+  }
+
+  fn headers(&self) -> HeaderMap {
+    let mut headers = HeaderMap::new();
+    headers.insert(
+      header::ACCEPT,
+      HeaderValue::from_static("application/vnd.github+json"),
+    );
+    headers.insert(
+      "X-GitHub-Api-Version",
+      HeaderValue::from_static("2022-11-28"),
+    );
+    if let Some(token) = &self.token {
+      if let Ok(mut value) = HeaderValue::from_str(&format!("Bearer {token}")) {
+        value.set_sensitive(true);
+        headers.insert(header::AUTHORIZATION, value);
+      }
+    }
+    headers
+  }
+
+  /// Fetches a single page of releases, or `None` if it was not modified since `etag`.
+  fn get_page(&self, url: &str, etag: Option<&str>) -> Result<Option<Page>, Error> {
+    let Some(resp) = get_if_modified(url, self.headers(), etag)? else {
+      return Ok(None);
+    };
+    let etag = etag_of(&resp);
+    let next = resp
+      .headers()
+      .get(header::LINK)
+      .and_then(|link| link.to_str().ok())
+      .and_then(next_page_url);
+    let text = resp.text().map_err(crate::http::Error::from)?;
+    Ok(Some(Page { text, etag, next }))
+  }
+}
+
+impl ReleaseProvider for GitHub {
+  fn name(&self) -> String {
+    self.repo.clone()
+  }
+
+  fn cache_key(&self) -> String {
+    format!(
+      "github-{}-{}",
+      self.api_url.host_str().unwrap_or_default(),
+      self.repo
+    )
+  }
+
+  /// Lists all the releases of the repository, following the pagination links.
+  ///
+  /// Only the first page is revalidated: a new release always shows up on it.
+  fn fetch(&self, etag: Option<&str>) -> Result<Fetch, Error> {
+    let first_page = format!("{}?per_page={PER_PAGE}", self.repo_url("releases"));
+    let Some(mut page) = self.get_page(&first_page, etag)? else {
+      return Ok(Fetch::NotModified);
+    };
+    let etag = page.etag.take();
+
+    let mut releases = vec![];
+    loop {
+      let text = page.text;
+      let page_releases: Vec<GitHubRelease> =
+        serde_json::from_str(&text[..]).map_err(|cause| DecodeError::from_serde(text, cause))?;
+      releases.extend(page_releases.into_iter().map(Release::from));
+
+      let Some(url) = page.next else { break };
+      page = self
+        .get_page(&url, None)?
+        .ok_or(Error::UnexpectedNotModified { url })?;
+    }
+
+    Ok(Fetch::Modified { releases, etag })
+  }
+}
+
+/// Extracts the `rel="next"` target out of a `Link` header, as returned by paginated GitHub endpoints.
+fn next_page_url(link_header: &str) -> Option<String> {
+  link_header.split(',').find_map(|part| {
+    let (target, params) = part.split_once(';')?;
+    let is_next = params
+      .split(';')
+      .any(|param| param.trim().replace(' ', "") == r#"rel="next""#);
+    is_next.then(|| {
+      target
+        .trim()
+        .trim_start_matches('<')
+        .trim_end_matches('>')
+        .to_string()
+    })
+  })
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use pretty_assertions::assert_eq;
+
+  #[test]
+  fn test_next_page_url() {
+    let link = r#"<https://api.github.com/repositories/1/releases?per_page=100&page=2>; rel="next", <https://api.github.com/repositories/1/releases?per_page=100&page=3>; rel="last""#;
+    assert_eq!(
+      next_page_url(link),
+      Some("https://api.github.com/repositories/1/releases?per_page=100&page=2".to_string())
+    );
+
+    let last_page = r#"<https://api.github.com/repositories/1/releases?per_page=100&page=1>; rel="prev", <https://api.github.com/repositories/1/releases?per_page=100&page=1>; rel="first""#;
+    assert_eq!(next_page_url(last_page), None);
+  }
+
+  #[test]
+  fn test_repo_url_for_enterprise_host() {
+    let github = GitHub::new(
+      Url::parse("https://github.example.com/api/v3/").unwrap(),
+      None,
+      "acme/pact/",
+    );
+    assert_eq!(
+      github.repo_url("releases"),
+      "https://github.example.com/api/v3/repos/acme/pact/releases"
+    );
+  }
+
+  #[test]
+  fn test_token_is_sent_as_bearer() {
+    let github = GitHub::new(
+      Url::parse("https://api.github.com").unwrap(),
+      Some("secret".to_string()),
+      "kadena-io/pact",
+    );
+    let headers = github.headers();
+    assert_eq!(headers.get(header::AUTHORIZATION).unwrap(), "Bearer secret");
+    assert!(headers.get(header::AUTHORIZATION).unwrap().is_sensitive());
+  }
+
+  #[test]
+  fn test_decode_release() {
+    let json = r#"[{
+      "tag_name": "v4.13.0",
+      "name": "Pact 4.13.0",
+      "draft": false,
+      "prerelease": false,
+      "created_at": "2024-09-30T12:00:00Z",
+      "published_at": "2024-10-01T08:30:00Z",
+      "body": "Bug fixes",
+      "assets": [{
+        "name": "pact-4.13.0-linux-22.04.zip",
+        "content_type": "application/zip",
+        "size": 43821112,
+        "download_count": 120,
+        "created_at": "2024-10-01T08:00:00Z",
+        "updated_at": "2024-10-01T08:10:00Z",
+        "url": "https://api.github.com/repos/kadena-io/pact/releases/assets/1234",
+        "browser_download_url": "https://github.com/kadena-io/pact/releases/download/v4.13.0/pact-4.13.0-linux-22.04.zip"
+      }]
+    }]"#;
+    let releases: Vec<Release> = serde_json::from_str::<Vec<GitHubRelease>>(json)
+      .unwrap()
+      .into_iter()
+      .map(Release::from)
+      .collect();
+    assert_eq!(releases.len(), 1);
+    assert_eq!(releases[0].tag, Version::parse("4.13.0").unwrap());
+    assert_eq!(releases[0].name.as_deref(), Some("Pact 4.13.0"));
+    assert_eq!(releases[0].markdown.as_deref(), Some("Bug fixes"));
+    assert_eq!(
+      releases[0].published_at.map(|x| x.to_rfc3339()),
+      Some("2024-10-01T08:30:00+00:00".to_string())
+    );
+    assert_eq!(releases[0].assets[0].size, Some(43_821_112));
+    assert_eq!(releases[0].assets[0].download_count, Some(120));
+    assert_eq!(
+      releases[0].assets[0].download_url.as_str(),
+      "https://github.com/kadena-io/pact/releases/download/v4.13.0/pact-4.13.0-linux-22.04.zip"
+    );
+    // With a token, the asset is downloaded through the API, so that private repositories serve it.
+    assert_eq!(
+      releases[0].assets[0].fetch_url(true).as_str(),
+      "https://api.github.com/repos/kadena-io/pact/releases/assets/1234"
+    );
+    assert_eq!(
+      releases[0].assets[0].fetch_url(false),
+      &releases[0].assets[0].download_url
+    );
+  }
+}
@@ -0,0 +1,989 @@
+mod asset_name;
+mod cache;
+mod github;
+mod static_index;
+mod ungh;
+
+pub use self::asset_name::{AssetName, AssetNaming, AssetTarget, NamingError, Rejection};
+use self::cache::{CachedIndex, IndexCache};
+use self::github::GitHub;
+use self::static_index::StaticIndex;
+use self::ungh::Ungh;
+use crate::checksum::Sha256;
+use crate::config::PactupConfig;
+use crate::http::header::{self, HeaderMap, HeaderValue};
+use crate::http::StatusCode;
+use crate::outln;
+use crate::release_backend::ReleaseBackend;
+use crate::sources::Source;
+use crate::{pretty_serde::DecodeError, version::Version};
+use chrono::{DateTime, Utc};
+use colored::Colorize;
+use log::debug;
+use serde::{Deserialize, Serialize};
+use std::cmp::Reverse;
+use std::collections::BTreeSet;
+use std::fmt::Debug;
+use std::sync::Arc;
+use url::Url;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Asset {
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub content_type: Option<String>,
+  /// The size of the asset in bytes.
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub size: Option<u64>,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub created_at: Option<DateTime<Utc>>,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub updated_at: Option<DateTime<Utc>>,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub download_count: Option<u64>,
+  pub download_url: Url,
+  /// The URL of the asset in the GitHub API, which serves the assets of private repositories to
+  /// the requests authenticated with a token.
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub api_url: Option<Url>,
+  /// The SHA-256 digest of the asset, when the provider publishes it.
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub sha256: Option<Sha256>,
+}
+
+impl Asset {
+  /// An asset known only by its download URL.
+  #[cfg(test)]
+  pub fn new(download_url: Url) -> Self {
+    Self {
+      content_type: None,
+      size: None,
+      created_at: None,
+      updated_at: None,
+      download_count: None,
+      download_url,
+      api_url: None,
+      sha256: None,
+    }
+  }
+
+  /// The URL to download the asset from: its API URL when a GitHub token is set, the only one
+  /// private repositories serve the asset at.
+  pub fn fetch_url(&self, authenticated: bool) -> &Url {
+    self
+      .api_url
+      .as_ref()
+      .filter(|_| authenticated)
+      .unwrap_or(&self.download_url)
+  }
+
+  /// The file name of the asset, taken from its download URL.
+  pub fn file_name(&self) -> Option<&str> {
+    self
+      .download_url
+      .path_segments()
+      .and_then(std::iter::Iterator::last)
+  }
+}
+
+/// A release, along with its metadata.
+///
+/// Metadata such as dates or descriptions are optional, since not every
+/// [`ReleaseProvider`] knows about them.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Release {
+  pub tag: Version,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub name: Option<String>,
+  pub draft: bool,
+  pub prerelease: bool,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub created_at: Option<DateTime<Utc>>,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub published_at: Option<DateTime<Utc>>,
+  /// The release notes, in Markdown.
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub markdown: Option<String>,
+  pub assets: Vec<Asset>,
+  /// The source the release was listed from, set by [`list_for_major`].
+  #[serde(skip)]
+  pub source: Option<Arc<Source>>,
+}
+
+impl Release {
+  /// How the assets of this release are named, according to its source.
+  pub fn naming(&self) -> AssetNaming {
+    self
+      .source
+      .as_ref()
+      .map(|source| source.naming.clone())
+      .unwrap_or_default()
+  }
+
+  /// Parses and checks every asset of this release against `target`, in order.
+  pub fn assess_assets(&self, target: &AssetTarget) -> Vec<(&Asset, Result<AssetName, Rejection>)> {
+    let naming = self.naming();
+    self
+      .assets
+      .iter()
+      .map(|asset| {
+        let verdict = asset
+          .file_name()
+          .ok_or(Rejection::UnrecognizedName)
+          .and_then(|file_name| naming.parse(file_name))
+          .and_then(|name| name.check(&self.tag, target).map(|()| name));
+        (asset, verdict)
+      })
+      .collect()
+  }
+
+  /// The best asset for `target`, according to [`AssetName::rank`]. Ties go to the first asset.
+  pub fn asset_for(&self, target: &AssetTarget) -> Option<&Asset> {
+    self
+      .assess_assets(target)
+      .into_iter()
+      .filter_map(|(asset, verdict)| verdict.ok().map(|name| (asset, name)))
+      .min_by_key(|(_, name)| Reverse(name.rank()))
+      .map(|(asset, _)| asset)
+  }
+
+  pub fn has_supported_asset(&self, target: &AssetTarget) -> bool {
+    self.asset_for(target).is_some()
+  }
+
+  pub fn is_nightly(&self) -> bool {
+    self.tag.is_nightly()
+  }
+}
+
+#[derive(Debug, thiserror::Error, miette::Diagnostic)]
+pub enum Error {
+  #[error("can't get remote versions file: {0}")]
+  #[diagnostic(transparent)]
+  Http(#[from] crate::http::Error),
+  #[error("can't decode remote versions file: {0}")]
+  #[diagnostic(transparent)]
+  Decode(#[from] DecodeError),
+  #[error("invalid asset url {url}: {source}")]
+  #[diagnostic(code(pactup::remote_pact_index::Error::InvalidAssetUrl))]
+  InvalidAssetUrl {
+    url: String,
+    source: url::ParseError,
+  },
+  #[error("can't reach {name} to list the available versions: {source}")]
+  #[diagnostic(code(pactup::remote_pact_index::Error::Unreachable))]
+  Unreachable {
+    name: String,
+    source: crate::http::Error,
+  },
+  #[error("can't reach {name} to refresh the versions cached on {}: {source}\nUse `--offline` to use the cached versions anyway.", fetched_at.format("%Y-%m-%d %H:%M"))]
+  #[diagnostic(code(pactup::remote_pact_index::Error::StaleCache))]
+  StaleCache {
+    name: String,
+    fetched_at: DateTime<Utc>,
+    source: crate::http::Error,
+  },
+  #[error("the versions of {name} are not cached yet, can't list them while offline")]
+  #[diagnostic(code(pactup::remote_pact_index::Error::NotCached))]
+  NotCached { name: String },
+  #[error("{url} answered 304 Not Modified to a request that didn't ask to revalidate anything")]
+  #[diagnostic(code(pactup::remote_pact_index::Error::UnexpectedNotModified))]
+  UnexpectedNotModified { url: String },
+  #[error(transparent)]
+  #[diagnostic(code(pactup::remote_pact_index::Error::Sources))]
+  Sources(#[from] crate::sources::Error),
+}
+
+/// The outcome of fetching releases from a [`ReleaseProvider`].
+#[derive(Debug)]
+pub enum Fetch {
+  /// The releases were unchanged since the given `ETag` was issued.
+  NotModified,
+  /// A fresh list of releases, with the `ETag` to revalidate it later, if any.
+  Modified {
+    releases: Vec<Release>,
+    etag: Option<String>,
+  },
+}
+
+/// A source of Pact releases, such as a GitHub repository or a static release index.
+///
+/// Providers are queried concurrently, hence the `Send + Sync` bound.
+pub trait ReleaseProvider: Send + Sync {
+  /// A human readable name of the source, used in messages.
+  fn name(&self) -> String;
+
+  /// A unique, file system friendly key used to cache the releases of this source.
+  fn cache_key(&self) -> String;
+
+  /// Lists all the releases this source knows about.
+  ///
+  /// When `etag` is given, the source may answer with [`Fetch::NotModified`]
+  /// if its releases didn't change since then.
+  fn fetch(&self, etag: Option<&str>) -> Result<Fetch, Error>;
+}
+
+/// Sends a conditional GET request, returning `None` when the server answers `304 Not Modified`.
+fn get_if_modified(
+  url: &str,
+  mut headers: HeaderMap,
+  etag: Option<&str>,
+) -> Result<Option<crate::http::Response>, Error> {
+  if let Some(value) = etag.and_then(|etag| HeaderValue::from_str(etag).ok()) {
+    headers.insert(header::IF_NONE_MATCH, value);
+  }
+  let resp = crate::http::get_with_headers(url, &headers)?;
+  if resp.status() == StatusCode::NOT_MODIFIED {
+    return Ok(None);
+  }
+  let resp = resp.error_for_status().map_err(crate::http::Error::from)?;
+  Ok(Some(resp))
+}
+
+fn etag_of(resp: &crate::http::Response) -> Option<String> {
+  resp
+    .headers()
+    .get(header::ETAG)
+    .and_then(|etag| etag.to_str().ok())
+    .map(String::from)
+}
+
+/// A release provider, along with the source it was configured from.
+type SourceProvider = (Arc<Source>, Box<dyn ReleaseProvider>);
+
+/// The release providers to query, in order, along with their source.
+///
+/// Sources limited to other major versions than `major` are left out.
+/// A configured release index replaces the sources altogether.
+pub fn providers(config: &PactupConfig, major: Option<u64>) -> Result<Vec<SourceProvider>, Error> {
+  if let Some(index_url) = &config.index_url {
+    let source = Source::new(index_url.as_str(), index_url.as_str(), vec![]);
+    let provider = StaticIndex::new(index_url.clone());
+    return Ok(vec![(Arc::new(source), Box::new(provider))]);
+  }
+
+  let providers = crate::sources::load(config)?
+    .into_iter()
+    .filter(|source| source.serves(major))
+    .map(|source| -> SourceProvider {
+      let provider: Box<dyn ReleaseProvider> = match (source.index_url(), config.release_backend) {
+        (Some(url), _) => Box::new(StaticIndex::new(url)),
+        (None, ReleaseBackend::Ungh) => Box::new(Ungh::new(&source.location)),
+        (None, ReleaseBackend::GitHub) => Box::new(GitHub::new(
+          config.github_api_url.clone(),
+          config.github_token(),
+          &source.location,
+        )),
+      };
+      (Arc::new(source), provider)
+    })
+    .collect();
+  Ok(providers)
+}
+
+/// Lists the releases of a single provider, going through the on-disk cache.
+///
+/// A cached list younger than the configured TTL is used as is. Older lists are
+/// revalidated with their `ETag`. In offline mode, only the cache is consulted.
+fn list_provider(
+  provider: &dyn ReleaseProvider,
+  config: &PactupConfig,
+) -> Result<Vec<Release>, Error> {
+  let cache = IndexCache::new(config.index_cache_dir());
+  let key = provider.cache_key();
+  let cached = cache.read(&key);
+
+  if config.offline {
+    debug!(
+      "Offline: reading the cached releases of {}",
+      provider.name()
+    );
+    return cached
+      .map(|cached| cached.releases)
+      .ok_or_else(|| Error::NotCached {
+        name: provider.name(),
+      });
+  }
+
+  if let Some(cached) = cached
+    .as_ref()
+    .filter(|x| x.is_fresh(config.index_cache_ttl()))
+  {
+    debug!("Using the cached releases of {}", provider.name());
+    return Ok(cached.releases.clone());
+  }
+
+  debug!("Fetching releases from {}", provider.name());
+  let etag = cached.as_ref().and_then(|cached| cached.etag.as_deref());
+  match provider.fetch(etag) {
+    Ok(Fetch::Modified { releases, etag }) => {
+      cache.write(&key, &CachedIndex::new(releases.clone(), etag));
+      Ok(releases)
+    }
+    Ok(Fetch::NotModified) => {
+      debug!("The releases of {} were not modified", provider.name());
+      let cached = cached.ok_or_else(|| Error::NotCached {
+        name: provider.name(),
+      })?;
+      let refreshed = CachedIndex::new(cached.releases, cached.etag);
+      cache.write(&key, &refreshed);
+      Ok(refreshed.releases)
+    }
+    Err(Error::Http(source)) => match cached {
+      Some(cached) => Err(Error::StaleCache {
+        name: provider.name(),
+        fetched_at: cached.fetched_at,
+        source,
+      }),
+      None => Err(Error::Unreachable {
+        name: provider.name(),
+        source,
+      }),
+    },
+    Err(err) => Err(err),
+  }
+}
+
+/// Lists the releases of the sources serving `major`, or of all of them when `None`,
+/// fetching them concurrently.
+///
+/// Releases are merged in source order: by default, the Pact 5 repository comes
+/// before the Pact 4 one, and a release index replaces both. When several sources publish
+/// the same tag, the release of the first source wins and the others are dropped.
+///
+/// A source that fails is reported and skipped, so the releases of the other
+/// sources are still returned. An error is returned only when all of them fail.
+pub fn list_for_major(config: &PactupConfig, major: Option<u64>) -> Result<Vec<Release>, Error> {
+  let providers = providers(config, major)?;
+  let results: Vec<_> = std::thread::scope(|scope| {
+    let handles: Vec<_> = providers
+      .iter()
+      .map(|(source, provider)| {
+        let handle = scope.spawn(|| {
+          let mut releases = list_provider(provider.as_ref(), config)?;
+          for release in &mut releases {
+            release.source = Some(source.clone());
+          }
+          Ok(releases)
+        });
+        (provider.name(), handle)
+      })
+      .collect();
+    handles
+      .into_iter()
+      .map(|(name, handle)| {
+        let result = handle
+          .join()
+          .expect("Can't join the release fetching thread");
+        (name, result)
+      })
+      .collect()
+  });
+  merge_releases(results, config)
+}
+
+/// Merges the releases listed by each provider, given as `(provider name, result)` pairs in provider order.
+fn merge_releases(
+  results: Vec<(String, Result<Vec<Release>, Error>)>,
+  config: &PactupConfig,
+) -> Result<Vec<Release>, Error> {
+  let sources = results.len();
+  let mut seen_tags = BTreeSet::new();
+  let mut releases = Vec::new();
+  let mut errors = Vec::new();
+  for (name, result) in results {
+    match result {
+      Ok(provider_releases) => releases.extend(
+        provider_releases
+          .into_iter()
+          .filter(|release| seen_tags.insert(release.tag.clone())),
+      ),
+      Err(err) => errors.push((name, err)),
+    }
+  }
+
+  let all_failed = !errors.is_empty() && errors.len() == sources;
+  let first_error = all_failed.then(|| errors.remove(0).1);
+  for (name, err) in errors {
+    outln!(
+      config,
+      Error,
+      "{} skipping the releases of {}: {}",
+      "warning:".yellow().bold(),
+      name,
+      err
+    );
+  }
+  match first_error {
+    Some(err) => Err(err),
+    None => Ok(releases),
+  }
+}
+
+/// The latest stable release among `releases`.
+pub fn pick_latest(releases: &[Release]) -> Option<&Release> {
+  releases
+    .iter()
+    .filter(|release| !release.draft && !release.prerelease && !release.is_nightly())
+    .max_by(|a, b| a.tag.cmp(&b.tag))
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::system_info::{get_platform, Platform, PlatformArch, PlatformOS};
+  use pretty_assertions::assert_eq;
+  use std::sync::atomic::{AtomicUsize, Ordering};
+
+  fn create_test_release(version: &str) -> Release {
+    Release {
+      tag: Version::parse(version).unwrap(),
+      name: None,
+      draft: false,
+      prerelease: false,
+      created_at: None,
+      published_at: None,
+      markdown: None,
+      assets: vec![],
+      source: None,
+    }
+  }
+
+  fn is_installable(release: &Release, platform: &Platform, file_name: &str) -> bool {
+    let target = AssetTarget::new(platform.clone());
+    AssetName::parse(file_name).is_some_and(|name| name.check(&release.tag, &target).is_ok())
+  }
+
+  #[test]
+  #[allow(clippy::too_many_lines)]
+  fn test_version_matcher_patterns() {
+    let test_cases = vec![
+      // Semver formats
+      (
+        "4.13.0",
+        PlatformOS::Linux,
+        PlatformArch::X64,
+        vec![
+          ("pact-4.13.0-linux-x64.tar.gz", true),
+          ("pact-4.13.0-linux-amd64.tar.gz", true),
+          ("pact-4.13.0-ubuntu-x64.tar.gz", true),
+          ("pact-4.13.0-ubuntu-x64-22.10.tar.gz", true),
+          ("pact-4.13-linux-x64.tar.gz", true),
+          ("pact-4.13-linux-x64-22.04.tar.gz", true),
+          ("pact-4-linux-x64.tar.gz", true),
+          ("pact-invalid-linux-x64.tar.gz", false),
+        ],
+      ),
+      // Tag formats (nightly)
+      (
+        "nightly",
+        PlatformOS::Linux,
+        PlatformArch::X64,
+        vec![
+          ("pact-nightly-linux-x64.tar.gz", true),
+          ("pact-nightly-ubuntu-amd64.tar.gz", true),
+          ("pact-dev-linux-x64.tar.gz", false),
+        ],
+      ),
+      // Tag formats (alpha)
+      (
+        "alpha",
+        PlatformOS::Linux,
+        PlatformArch::X64,
+        vec![
+          ("pact-alpha-linux-x64.tar.gz", true),
+          ("pact-alpha-ubuntu-amd64.tar.gz", true),
+          ("pact-beta-linux-x64.tar.gz", false),
+        ],
+      ),
+      // MacOS formats
+      (
+        "4.13.0",
+        PlatformOS::MacOS,
+        PlatformArch::Arm64,
+        vec![
+          ("pact-4.13.0-darwin-arm64.tar.gz", true),
+          ("pact-4.13.0-darwin-aarch64.tar.gz", true),
+          ("pact-4.13.0-osx-arm64.tar.gz", true),
+          ("pact-4.13.0-macos-aarch64.tar.gz", true),
+          ("pact-4.13.0-aarch64-macos.tar.gz", true),
+          ("pact-4.13.0-aarch64-osx.tar.gz", true),
+          ("pact-4.13.0-aarch64-darwin.tar.gz", true),
+          ("pact-4.13.0-darwin-x64.tar.gz", false),
+        ],
+      ),
+      // Windows formats
+      (
+        "4.13.0",
+        PlatformOS::Windows,
+        PlatformArch::X64,
+        vec![
+          ("pact-4.13.0-windows-x64.zip", true),
+          ("pact-4.13.0-win-amd64.zip", true),
+          ("pact-4.13.0-windows-x86_64.zip", true),
+          ("pact-4.13.0-windows-arm64.zip", false),
+        ],
+      ),
+      // Special tags
+      (
+        "latest",
+        PlatformOS::Linux,
+        PlatformArch::X64,
+        vec![
+          ("pact-latest-linux-x64.tar.gz", true),
+          ("pact-latest-ubuntu-amd64.tar.gz", true),
+        ],
+      ),
+      // Development tags
+      (
+        "dev",
+        PlatformOS::Linux,
+        PlatformArch::X64,
+        vec![
+          ("pact-dev-linux-x64.tar.gz", true),
+          ("pact-dev-ubuntu-amd64.tar.gz", true),
+        ],
+      ),
+      // Release candidate tags
+      (
+        "rc1",
+        PlatformOS::Linux,
+        PlatformArch::X64,
+        vec![
+          ("pact-rc1-linux-x64.tar.gz", true),
+          ("pact-rc1-ubuntu-amd64.tar.gz", true),
+        ],
+      ),
+    ];
+
+    for (version, os, arch, test_files) in test_cases {
+      let release = create_test_release(version);
+      let platform = Platform(os, arch);
+      for (test_file, should_match) in test_files {
+        assert_eq!(
+          is_installable(&release, &platform, test_file),
+          should_match,
+          "Failed for {} on {} with test file {}",
+          version,
+          platform,
+          test_file
+        );
+      }
+    }
+  }
+  #[test]
+  fn test_version_matcher_patterns_with_platform_version() {
+    let test_cases = vec![
+      (
+        "4.13.0",
+        PlatformOS::Linux,
+        PlatformArch::X64,
+        vec![
+          ("pact-4.13.0-linux-20.04.tar.gz", true),
+          ("pact-4.13.0-ubuntu-22.04.tar.gz", true),
+          ("pact-4.13.0-linux-20.04-x64.tar.gz", true),
+          ("pact-4.13.0-linux-20.04-amd64.tar.gz", true),
+          ("pact-4.13.0-linux.tar.gz", true), // No platform version or arch
+          ("pact-4.13.0-linux-invalid.tar.gz", false),
+        ],
+      ),
+      // Test x64 default when no arch specified
+      (
+        "4.13.0",
+        PlatformOS::Linux,
+        PlatformArch::X64,
+        vec![
+          ("pact-4.13.0-linux.tar.gz", true),
+          ("pact-4.13.0-ubuntu.tar.gz", true),
+          ("pact-4.13.0-linux-20.04.tar.gz", true),
+        ],
+      ),
+      // Test arm64 requires explicit specification
+      (
+        "4.13.0",
+        PlatformOS::Linux,
+        PlatformArch::Arm64,
+        vec![
+          ("pact-4.13.0-linux-arm64.tar.gz", true),
+          ("pact-4.13.0-linux-aarch64.tar.gz", true),
+          ("pact-4.13.0-linux.tar.gz", false), // Should not match without arch
+        ],
+      ),
+    ];
+
+    for (version, os, arch, test_files) in test_cases {
+      let release = create_test_release(version);
+      let platform = Platform(os, arch);
+      for (test_file, should_match) in test_files {
+        assert_eq!(
+          is_installable(&release, &platform, test_file),
+          should_match,
+          "Failed for {} on {} with test file {}",
+          version,
+          platform,
+          test_file
+        );
+      }
+    }
+  }
+
+  #[test]
+  #[cfg(target_os = "linux")]
+  fn test_asset_fallback_to_x64_on_linux() {
+    let mut release = create_test_release("4.13.0");
+    release.assets = vec![Asset::new(
+      Url::parse("https://example.com/download/pact-4.13.0-linux-20.04.tar.gz").unwrap(),
+    )];
+
+    let asset = release.asset_for(&AssetTarget::new(get_platform()));
+    assert!(asset.is_some());
+  }
+
+  #[test]
+  fn test_asset_for_current_platform() {
+    let mut release = create_test_release("4.13.0");
+    let platform = get_platform();
+    let asset_name = match platform {
+      Platform(PlatformOS::MacOS, PlatformArch::Arm64) => "pact-4.13.0-darwin-arm64.tar.gz",
+      Platform(PlatformOS::MacOS, PlatformArch::X64) => "pact-4.13.0-darwin-x64.tar.gz",
+      Platform(PlatformOS::Windows, _) => "pact-4.13.0-windows-x64.zip",
+      _ => "pact-4.13.0-linux-x64.tar.gz", // default for tests
+    };
+
+    release.assets = vec![Asset::new(
+      Url::parse(&format!("https://example.com/download/{asset_name}")).unwrap(),
+    )];
+
+    let target = AssetTarget::new(platform);
+    assert!(release.has_supported_asset(&target));
+    assert!(release.asset_for(&target).is_some());
+  }
+
+  #[test]
+  fn test_unsupported_platform() {
+    let mut release = create_test_release("4.13.0");
+    release.assets = vec![Asset::new(
+      Url::parse("https://example.com/download/pact-4.13.0-windows-x64.zip").unwrap(),
+    )];
+    let platform = Platform(PlatformOS::Windows, PlatformArch::S390x);
+    assert_eq!(release.asset_for(&AssetTarget::new(platform)), None);
+  }
+
+  #[test]
+  fn test_asset_for_platform_prefers_specific_assets() {
+    let mut release = create_test_release("4.13.0");
+    release.assets = [
+      "pact-4.13.0-linux-20.04.zip",
+      "pact-4.13.0-linux-22.04.zip",
+      "pact-4.13.0-linux-x64-22.04.zip",
+      "pact-4.13.0-linux-x64-22.04.tar.gz",
+      "pact-4.13.0-linux-aarch64.tar.gz",
+      "pact-4.13.0-darwin-aarch64.tar.gz",
+    ]
+    .iter()
+    .map(|name| Asset::new(Url::parse(&format!("https://example.com/{name}")).unwrap()))
+    .collect();
+
+    let file_name = |platform| {
+      release
+        .asset_for(&AssetTarget::new(platform))
+        .and_then(Asset::file_name)
+    };
+    assert_eq!(
+      file_name(Platform(PlatformOS::Linux, PlatformArch::X64)),
+      Some("pact-4.13.0-linux-x64-22.04.tar.gz")
+    );
+    assert_eq!(
+      file_name(Platform(PlatformOS::Linux, PlatformArch::Arm64)),
+      Some("pact-4.13.0-linux-aarch64.tar.gz")
+    );
+    assert_eq!(
+      file_name(Platform(PlatformOS::Windows, PlatformArch::X64)),
+      None
+    );
+
+    let verdicts: Vec<_> = release
+      .assess_assets(&AssetTarget::new(Platform(
+        PlatformOS::Linux,
+        PlatformArch::Arm64,
+      )))
+      .into_iter()
+      .map(|(_, verdict)| verdict.err())
+      .collect();
+    assert_eq!(verdicts[0], Some(Rejection::MissingArch));
+    assert_eq!(verdicts[4], None);
+    assert_eq!(
+      verdicts[5],
+      Some(Rejection::OsMismatch {
+        found: PlatformOS::MacOS
+      })
+    );
+  }
+
+  #[test]
+  fn test_special_version_tags() {
+    let test_cases = vec![
+      ("nightly", "pact-nightly-linux-x64.tar.gz"),
+      ("alpha", "pact-alpha-linux-x64.tar.gz"),
+      ("beta", "pact-beta-linux-x64.tar.gz"),
+      ("rc1", "pact-rc1-linux-x64.tar.gz"),
+      ("latest", "pact-latest-linux-x64.tar.gz"),
+      ("dev", "pact-dev-linux-x64.tar.gz"),
+    ];
+
+    for (version, test_file) in test_cases {
+      let release = create_test_release(version);
+      let platform = Platform(PlatformOS::Linux, PlatformArch::X64);
+      assert!(
+        is_installable(&release, &platform, test_file),
+        "Failed for version {version} on test file {test_file}"
+      );
+    }
+  }
+
+  /// A provider answering with canned responses, counting the requests it receives.
+  struct FakeProvider {
+    response: fn(Option<&str>) -> Result<Fetch, Error>,
+    requests: AtomicUsize,
+  }
+
+  impl FakeProvider {
+    fn new(response: fn(Option<&str>) -> Result<Fetch, Error>) -> Self {
+      Self {
+        response,
+        requests: AtomicUsize::new(0),
+      }
+    }
+  }
+
+  impl ReleaseProvider for FakeProvider {
+    fn name(&self) -> String {
+      "fake".to_string()
+    }
+
+    fn cache_key(&self) -> String {
+      "fake".to_string()
+    }
+
+    fn fetch(&self, etag: Option<&str>) -> Result<Fetch, Error> {
+      self.requests.fetch_add(1, Ordering::SeqCst);
+      (self.response)(etag)
+    }
+  }
+
+  fn modified() -> FakeProvider {
+    FakeProvider::new(|etag| {
+      assert_eq!(etag, None);
+      Ok(Fetch::Modified {
+        releases: vec![create_test_release("4.13.0")],
+        etag: Some("\"v1\"".to_string()),
+      })
+    })
+  }
+
+  fn not_modified() -> FakeProvider {
+    FakeProvider::new(|etag| {
+      assert_eq!(etag, Some("\"v1\""));
+      Ok(Fetch::NotModified)
+    })
+  }
+
+  fn unreachable() -> FakeProvider {
+    FakeProvider::new(|_| {
+      let file = tempfile::tempdir().unwrap().path().join("missing.json");
+      let url = Url::from_file_path(file).unwrap();
+      Err(crate::http::get(url.as_str()).unwrap_err().into())
+    })
+  }
+
+  fn create_test_config(cache_dir: &std::path::Path) -> PactupConfig {
+    PactupConfig::default().with_cache_dir(Some(cache_dir.to_path_buf()))
+  }
+
+  #[test]
+  fn test_list_provider_uses_fresh_cache() {
+    let cache_dir = tempfile::tempdir().unwrap();
+    let config = create_test_config(cache_dir.path());
+    let provider = modified();
+
+    let releases = list_provider(&provider, &config).unwrap();
+    assert_eq!(releases, vec![create_test_release("4.13.0")]);
+    let releases = list_provider(&provider, &config).unwrap();
+    assert_eq!(releases, vec![create_test_release("4.13.0")]);
+    assert_eq!(provider.requests.load(Ordering::SeqCst), 1);
+  }
+
+  #[test]
+  fn test_list_provider_revalidates_stale_cache() {
+    let cache_dir = tempfile::tempdir().unwrap();
+    let config = create_test_config(cache_dir.path());
+    list_provider(&modified(), &config).unwrap();
+
+    let config = config.with_index_cache_ttl(0);
+    let provider = not_modified();
+    let releases = list_provider(&provider, &config).unwrap();
+    assert_eq!(releases, vec![create_test_release("4.13.0")]);
+    assert_eq!(provider.requests.load(Ordering::SeqCst), 1);
+  }
+
+  #[test]
+  fn test_list_provider_stale_cache_without_network() {
+    let cache_dir = tempfile::tempdir().unwrap();
+    let config = create_test_config(cache_dir.path());
+    list_provider(&modified(), &config).unwrap();
+
+    let mut config = config.with_index_cache_ttl(0);
+    let result = list_provider(&unreachable(), &config);
+    assert!(matches!(result, Err(Error::StaleCache { .. })));
+
+    config.offline = true;
+    let provider = unreachable();
+    let releases = list_provider(&provider, &config).unwrap();
+    assert_eq!(releases, vec![create_test_release("4.13.0")]);
+    assert_eq!(provider.requests.load(Ordering::SeqCst), 0);
+  }
+
+  #[test]
+  fn test_list_provider_offline_without_cache() {
+    let cache_dir = tempfile::tempdir().unwrap();
+    let mut config = create_test_config(cache_dir.path());
+    config.offline = true;
+    let result = list_provider(&modified(), &config);
+    assert!(matches!(result, Err(Error::NotCached { .. })));
+  }
+
+  #[test]
+  fn test_pick_latest_skips_prereleases_and_nightlies() {
+    let mut prerelease = create_test_release("5.0.0");
+    prerelease.prerelease = true;
+    let releases = vec![
+      create_test_release("4.12.0"),
+      create_test_release("4.13.0"),
+      prerelease,
+      create_test_release("nightly"),
+    ];
+    assert_eq!(
+      pick_latest(&releases).map(|x| x.tag.clone()),
+      Some(Version::parse("4.13.0").unwrap())
+    );
+  }
+
+  #[test]
+  fn test_providers_are_limited_to_the_requested_major() {
+    let base_dir = tempfile::tempdir().unwrap();
+    let config = PactupConfig::default().with_base_dir(Some(base_dir.path().to_path_buf()));
+    let mut sources = crate::sources::builtin(&config);
+    sources.insert(
+      0,
+      crate::sources::Source::new("internal", "acme/pact", vec![5]),
+    );
+    crate::sources::save(&config, &sources).unwrap();
+
+    let names = |major| -> Vec<String> {
+      providers(&config, major)
+        .unwrap()
+        .into_iter()
+        .map(|(source, _)| source.name.clone())
+        .collect()
+    };
+    assert_eq!(names(Some(4)), vec!["pact-4"]);
+    assert_eq!(names(Some(5)), vec!["internal", "pact-5", "pact-4"]);
+    assert_eq!(names(None), vec!["internal", "pact-5", "pact-4"]);
+  }
+
+  #[test]
+  fn test_merge_releases_drops_duplicate_tags() {
+    let mut pact_5_release = create_test_release("4.13.0");
+    pact_5_release.prerelease = true;
+    let results = vec![
+      (
+        "pact-5".to_string(),
+        Ok(vec![pact_5_release.clone(), create_test_release("5.0.0")]),
+      ),
+      (
+        "pact".to_string(),
+        Ok(vec![
+          create_test_release("4.13.0"),
+          create_test_release("4.12.0"),
+        ]),
+      ),
+    ];
+    let releases = merge_releases(results, &PactupConfig::default()).unwrap();
+    assert_eq!(
+      releases,
+      vec![
+        pact_5_release,
+        create_test_release("5.0.0"),
+        create_test_release("4.12.0"),
+      ]
+    );
+  }
+
+  #[test]
+  fn test_merge_releases_keeps_partial_results() {
+    let results = vec![
+      (
+        "pact-5".to_string(),
+        Err(Error::NotCached {
+          name: "pact-5".to_string(),
+        }),
+      ),
+      ("pact".to_string(), Ok(vec![create_test_release("4.13.0")])),
+    ];
+    let releases = merge_releases(results, &PactupConfig::default()).unwrap();
+    assert_eq!(releases, vec![create_test_release("4.13.0")]);
+  }
+
+  #[test]
+  fn test_merge_releases_fails_when_all_sources_fail() {
+    let results = vec![
+      (
+        "pact-5".to_string(),
+        Err(Error::NotCached {
+          name: "pact-5".to_string(),
+        }),
+      ),
+      (
+        "pact".to_string(),
+        Err(Error::NotCached {
+          name: "pact".to_string(),
+        }),
+      ),
+    ];
+    let result = merge_releases(results, &PactupConfig::default());
+    assert!(matches!(result, Err(Error::NotCached { name }) if name == "pact-5"));
+  }
+
+  #[cfg(target_os = "linux")]
+  fn list_repo(repo: &str) -> Vec<Release> {
+    match Ungh::new(repo).fetch(None).expect("Can't get HTTP data") {
+      Fetch::Modified { releases, .. } => releases,
+      Fetch::NotModified => unreachable!("an unconditional request can't be not modified"),
+    }
+  }
+
+  #[cfg(target_os = "linux")]
+  #[test]
+  fn test_list() {
+    let repo = "kadena-io/pact";
+    let expected_version = Version::parse("4.13.0").unwrap();
+    let mut versions = list_repo(repo);
+    let release = versions
+      .drain(..)
+      .find(|x| x.tag == expected_version)
+      .map(|x| x.tag);
+    assert_eq!(release, Some(expected_version));
+    assert!(!release.unwrap().is_nightly());
+
+    let repo = "kadena-io/pact-5";
+    let expected_version = Version::parse("nightly").unwrap();
+    let mut versions = list_repo(repo);
+    let release = versions
+      .drain(..)
+      .find(|x| x.tag == expected_version)
+      .map(|x| x.tag);
+    assert_eq!(release, Some(expected_version));
+    assert!(release.unwrap().is_nightly());
+  }
+}
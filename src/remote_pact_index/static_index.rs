@@ -0,0 +1,198 @@
+//! Reads releases from a static `index.json` file, served over `https://` or from a `file://` URL.
+//!
+//! The index lists every release with its flags and assets:
+//!
+//! ```json
+//! {
+//!   "releases": [
+//!     {
+//!       "tag": "v4.13.0",
+//!       "name": "Pact 4.13.0",
+//!       "draft": false,
+//!       "prerelease": false,
+//!       "publishedAt": "2024-10-01T08:30:00Z",
+//!       "assets": [
+//!         { "url": "v4.13.0/pact-4.13.0-linux-22.04.zip", "size": 43821112, "sha256": "9f2c…" },
+//!         { "url": "https://artifacts.example.com/pact/pact-4.13.0-darwin-aarch64.tar.gz" }
+//!       ]
+//!     }
+//!   ]
+//! }
+//! ```
+//!
+//! `draft` and `prerelease` are optional and default to `false`. The release `name`,
+//! `createdAt`, `publishedAt` and `markdown` notes, as well as the asset `size`,
+//! `sha256` digest, `contentType`, `createdAt`, `updatedAt` and `downloadCount`, are optional too.
+//! When given, the digest is checked against the downloaded asset.
+//! Asset URLs may be relative, in which case they are resolved against the index URL.
+
+use super::{etag_of, get_if_modified, Asset, Error, Fetch, Release, ReleaseProvider};
+use crate::checksum::Sha256;
+use crate::http::header::HeaderMap;
+use crate::pretty_serde::DecodeError;
+use crate::version::Version;
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use url::Url;
+
+#[derive(Debug, Deserialize)]
+struct IndexFile {
+  releases: Vec<IndexRelease>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct IndexRelease {
+  tag: Version,
+  name: Option<String>,
+  #[serde(default)]
+  draft: bool,
+  #[serde(default)]
+  prerelease: bool,
+  created_at: Option<DateTime<Utc>>,
+  published_at: Option<DateTime<Utc>>,
+  markdown: Option<String>,
+  #[serde(default)]
+  assets: Vec<IndexAsset>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct IndexAsset {
+  #[serde(alias = "downloadUrl")]
+  url: String,
+  content_type: Option<String>,
+  size: Option<u64>,
+  created_at: Option<DateTime<Utc>>,
+  updated_at: Option<DateTime<Utc>>,
+  download_count: Option<u64>,
+  sha256: Option<Sha256>,
+}
+
+#[derive(Debug)]
+pub struct StaticIndex {
+  url: Url,
+}
+
+impl StaticIndex {
+  pub fn new(url: Url) -> Self {
+    Self { url }
+  }
+
+  fn parse(&self, text: String) -> Result<Vec<Release>, Error> {
+    let index: IndexFile =
+      serde_json::from_str(&text[..]).map_err(|cause| DecodeError::from_serde(text, cause))?;
+
+    index
+      .releases
+      .into_iter()
+      .map(|release| {
+        let assets = release
+          .assets
+          .into_iter()
+          .map(|asset| {
+            let download_url =
+              self
+                .url
+                .join(&asset.url)
+                .map_err(|source| Error::InvalidAssetUrl {
+                  url: asset.url,
+                  source,
+                })?;
+            Ok(Asset {
+              content_type: asset.content_type,
+              size: asset.size,
+              created_at: asset.created_at,
+              updated_at: asset.updated_at,
+              download_count: asset.download_count,
+              download_url,
+              api_url: None,
+              sha256: asset.sha256,
+            })
+          })
+          .collect::<Result<_, Error>>()?;
+        Ok(Release {
+          tag: release.tag,
+          name: release.name,
+          draft: release.draft,
+          prerelease: release.prerelease,
+          created_at: release.created_at,
+          published_at: release.published_at,
+          markdown: release.markdown,
+          assets,
+          source: None,
+        })
+      })
+      .collect()
+  }
+}
+
+impl ReleaseProvider for StaticIndex {
+  fn name(&self) -> String {
+    self.url.to_string()
+  }
+
+  fn cache_key(&self) -> String {
+    format!("index-{}", self.url)
+  }
+
+  fn fetch(&self, etag: Option<&str>) -> Result<Fetch, Error> {
+    let Some(resp) = get_if_modified(self.url.as_str(), HeaderMap::new(), etag)? else {
+      return Ok(Fetch::NotModified);
+    };
+    let etag = etag_of(&resp);
+    let text = resp.text().map_err(crate::http::Error::from)?;
+    Ok(Fetch::Modified {
+      releases: self.parse(text)?,
+      etag,
+    })
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use pretty_assertions::assert_eq;
+
+  const INDEX: &str = r#"{
+    "releases": [
+      {
+        "tag": "v4.13.0",
+        "publishedAt": "2024-10-01T08:30:00Z",
+        "assets": [
+          { "url": "v4.13.0/pact-4.13.0-linux-22.04.zip", "size": 43821112 },
+          { "url": "https://artifacts.example.com/pact/pact-4.13.0-darwin-aarch64.tar.gz" }
+        ]
+      },
+      { "tag": "v5.0.0", "prerelease": true, "assets": [] },
+      { "tag": "nightly", "assets": [] }
+    ]
+  }"#;
+
+  #[test]
+  fn test_list_from_file_url() {
+    let dir = tempfile::tempdir().unwrap();
+    let index_path = dir.path().join("index.json");
+    std::fs::write(&index_path, INDEX).unwrap();
+    let index = StaticIndex::new(Url::from_file_path(&index_path).unwrap());
+
+    let Fetch::Modified { releases, etag } = index.fetch(None).unwrap() else {
+      panic!("an unconditional request can't be not modified");
+    };
+    assert_eq!(etag, None);
+    assert_eq!(releases.len(), 3);
+    assert!(releases[1].prerelease);
+    assert!(!releases[0].draft);
+
+    let local_asset =
+      Url::from_file_path(dir.path().join("v4.13.0/pact-4.13.0-linux-22.04.zip")).unwrap();
+    assert_eq!(releases[0].assets[0].download_url, local_asset);
+    assert_eq!(releases[0].assets[0].size, Some(43_821_112));
+    assert!(releases[0].published_at.is_some());
+    assert_eq!(releases[1].published_at, None);
+    assert_eq!(
+      releases[0].assets[1].download_url.as_str(),
+      "https://artifacts.example.com/pact/pact-4.13.0-darwin-aarch64.tar.gz"
+    );
+  }
+}
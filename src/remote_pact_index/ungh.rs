@@ -0,0 +1,56 @@
+//! Fetches releases through the [ungh](https://github.com/unjs/ungh) GitHub proxy.
+
+use super::{etag_of, get_if_modified, Error, Fetch, Release, ReleaseProvider};
+use crate::http::header::HeaderMap;
+use crate::pretty_serde::DecodeError;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct UnghReleasesResponse {
+  releases: Vec<Release>,
+}
+
+fn format_ungh_url(repo_url: &str, path: &str) -> String {
+  format!(
+    "https://ungh.sashoush.dev/repos/{}/{}",
+    repo_url.trim_end_matches('/'),
+    path
+  )
+}
+
+#[derive(Debug)]
+pub struct Ungh {
+  repo: String,
+}
+
+impl Ungh {
+  pub fn new(repo: impl Into<String>) -> Self {
+    Self { repo: repo.into() }
+  }
+}
+
+impl ReleaseProvider for Ungh {
+  fn name(&self) -> String {
+    self.repo.clone()
+  }
+
+  fn cache_key(&self) -> String {
+    format!("ungh-{}", self.repo)
+  }
+
+  fn fetch(&self, etag: Option<&str>) -> Result<Fetch, Error> {
+    let releases_url = format_ungh_url(&self.repo, "releases");
+    let Some(resp) = get_if_modified(&releases_url, HeaderMap::new(), etag)? else {
+      return Ok(Fetch::NotModified);
+    };
+    let etag = etag_of(&resp);
+    let text = resp.text().map_err(crate::http::Error::from)?;
+    let ungh_response: UnghReleasesResponse =
+      serde_json::from_str(&text[..]).map_err(|cause| DecodeError::from_serde(text, cause))?;
+
+    Ok(Fetch::Modified {
+      releases: ungh_response.releases,
+      etag,
+    })
+  }
+}
@@ -0,0 +1,176 @@
+//! [Minisign](https://jedisct1.github.io/minisign/) signatures of release assets.
+//!
+//! A source can pin the public keys its releases are signed with. The signature of an asset is
+//! the `<asset>.minisig` file published next to it, and is verified as the asset downloads.
+
+use minisign_verify::{PublicKey, StreamVerifier};
+use std::fmt;
+use std::io::Read;
+
+/// The file name of the detached signature of the asset named `file_name`.
+pub fn file_name_for(file_name: &str) -> String {
+  format!("{file_name}.minisig")
+}
+
+/// Checks that `key` is a minisign public key, e.g. `RWQf6LRCGA9i53mlYecO4IzT51TGPpvWucNSCh1CBM0QTaLn73Y7GFO3`.
+pub fn parse_public_key(key: &str) -> Result<PublicKey, minisign_verify::Error> {
+  PublicKey::from_base64(key.trim())
+}
+
+/// A detached signature, along with the public keys it may have been made with.
+#[derive(Clone)]
+pub struct Signature {
+  inner: minisign_verify::Signature,
+  public_keys: Vec<PublicKey>,
+  /// Where the signature comes from, e.g. `pact-5.1.0-linux-x64.tar.gz.minisig`.
+  pub origin: String,
+}
+
+impl fmt::Debug for Signature {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    f.debug_struct("Signature")
+      .field("origin", &self.origin)
+      .field("trusted_comment", &self.inner.trusted_comment())
+      .finish_non_exhaustive()
+  }
+}
+
+impl Signature {
+  /// Decodes the contents of a `.minisig` file, to be verified with one of `public_keys`.
+  pub fn decode(
+    text: &str,
+    public_keys: &[String],
+    origin: impl Into<String>,
+  ) -> Result<Self, Error> {
+    let origin = origin.into();
+    let inner =
+      minisign_verify::Signature::decode(text).map_err(|source| Error::InvalidSignatureFile {
+        origin: origin.clone(),
+        source,
+      })?;
+    let public_keys = public_keys
+      .iter()
+      .map(|key| {
+        parse_public_key(key).map_err(|source| Error::InvalidPublicKey {
+          key: key.clone(),
+          source,
+        })
+      })
+      .collect::<Result<_, _>>()?;
+    Ok(Self {
+      inner,
+      public_keys,
+      origin,
+    })
+  }
+
+  /// Starts verifying the signed data as it streams, with the pinned key the signature was made with.
+  fn verifier(&self) -> Result<StreamVerifier<'_>, Error> {
+    self
+      .public_keys
+      .iter()
+      .find_map(|key| key.verify_stream(&self.inner).ok())
+      .ok_or_else(|| Error::UnknownKey {
+        origin: self.origin.clone(),
+      })
+  }
+}
+
+/// A reader verifying the signature of everything read through it, if there is one.
+pub struct VerifyingReader<'a, R> {
+  inner: R,
+  verifier: Option<(StreamVerifier<'a>, &'a Signature)>,
+}
+
+impl<'a, R: Read> VerifyingReader<'a, R> {
+  pub fn new(inner: R, signature: Option<&'a Signature>) -> Result<Self, Error> {
+    let verifier = signature
+      .map(|signature| Ok::<_, Error>((signature.verifier()?, signature)))
+      .transpose()?;
+    Ok(Self { inner, verifier })
+  }
+
+  /// Checks the signature against the data read so far, which must be all of it.
+  pub fn finish(self) -> Result<(), Error> {
+    let Some((mut verifier, signature)) = self.verifier else {
+      return Ok(());
+    };
+    verifier.finalize().map_err(|source| Error::Mismatch {
+      origin: signature.origin.clone(),
+      source,
+    })
+  }
+}
+
+impl<R: Read> Read for VerifyingReader<'_, R> {
+  fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+    let count = self.inner.read(buf)?;
+    if let Some((verifier, _)) = &mut self.verifier {
+      verifier.update(&buf[..count]);
+    }
+    Ok(count)
+  }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+  #[error("Invalid public key {key}: {source}")]
+  InvalidPublicKey {
+    key: String,
+    source: minisign_verify::Error,
+  },
+  #[error("Can't decode the signature {origin}: {source}")]
+  InvalidSignatureFile {
+    origin: String,
+    source: minisign_verify::Error,
+  },
+  #[error("The signature {origin} wasn't made with any of the public keys pinned for the source")]
+  UnknownKey { origin: String },
+  #[error("The signature {origin} doesn't match the downloaded archive: {source}")]
+  Mismatch {
+    origin: String,
+    source: minisign_verify::Error,
+  },
+}
+
+#[cfg(test)]
+pub mod tests {
+  use super::*;
+
+  /// Signs `data` with a new key pair, returning the public key and the `.minisig` contents.
+  pub fn sign(data: &[u8]) -> (String, String) {
+    let key_pair = minisign::KeyPair::generate_unencrypted_keypair().unwrap();
+    let signature = minisign::sign(Some(&key_pair.pk), &key_pair.sk, data, None, None).unwrap();
+    (key_pair.pk.to_base64(), signature.to_string())
+  }
+
+  fn verify(signature: &Signature, mut data: &[u8]) -> Result<(), Error> {
+    let mut reader = VerifyingReader::new(&mut data, Some(signature))?;
+    std::io::copy(&mut reader, &mut std::io::sink()).unwrap();
+    reader.finish()
+  }
+
+  #[test]
+  fn test_verify() {
+    let (public_key, minisig) = sign(b"pact");
+    let (other_key, _) = sign(b"pact");
+
+    let signature =
+      Signature::decode(&minisig, &[other_key.clone(), public_key], "pact.minisig").unwrap();
+    assert!(verify(&signature, b"pact").is_ok());
+    assert!(matches!(
+      verify(&signature, b"tampered"),
+      Err(Error::Mismatch { .. })
+    ));
+
+    let signature = Signature::decode(&minisig, &[other_key], "pact.minisig").unwrap();
+    assert!(matches!(
+      verify(&signature, b"pact"),
+      Err(Error::UnknownKey { .. })
+    ));
+    assert!(matches!(
+      Signature::decode("garbage", &[], "pact.minisig"),
+      Err(Error::InvalidSignatureFile { .. })
+    ));
+  }
+}
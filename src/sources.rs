@@ -0,0 +1,211 @@
+//! The ordered list of sources Pact releases are fetched from.
+//!
+//! Sources are stored in `sources.json`, in the pactup directory, and managed with `pactup source`.
+//! Until it exists, pactup queries the two built-in sources: `pact-5` and `pact-4`.
+//! The repositories of the built-in sources always come from `--pact-5x-repo` and `--pact-4x-repo`.
+
+use crate::config::PactupConfig;
+use crate::remote_pact_index::AssetNaming;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use url::Url;
+
+pub const PACT_5: &str = "pact-5";
+pub const PACT_4: &str = "pact-4";
+
+/// A named source of Pact releases.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Source {
+  pub name: String,
+  /// A GitHub repository (`owner/repo`), or the URL of a static release index.
+  pub location: String,
+  /// The major versions this source publishes. Empty means any version.
+  #[serde(default, skip_serializing_if = "Vec::is_empty")]
+  pub majors: Vec<u64>,
+  /// How the release assets of this source are named.
+  #[serde(default, skip_serializing_if = "AssetNaming::is_default")]
+  pub naming: AssetNaming,
+  /// The minisign public keys the release assets of this source are signed with.
+  /// When any is pinned, assets must come with a valid signature.
+  #[serde(default, skip_serializing_if = "Vec::is_empty")]
+  pub public_keys: Vec<String>,
+}
+
+impl Source {
+  pub fn new(name: impl Into<String>, location: impl Into<String>, majors: Vec<u64>) -> Self {
+    Self {
+      name: name.into(),
+      location: location.into(),
+      majors,
+      naming: AssetNaming::default(),
+      public_keys: vec![],
+    }
+  }
+
+  #[must_use]
+  pub fn with_naming(mut self, naming: AssetNaming) -> Self {
+    self.naming = naming;
+    self
+  }
+
+  #[must_use]
+  pub fn with_public_keys(mut self, public_keys: Vec<String>) -> Self {
+    self.public_keys = public_keys;
+    self
+  }
+
+  pub fn is_builtin(&self) -> bool {
+    is_builtin(&self.name)
+  }
+
+  /// Whether this source should be queried for versions of `major`, or for any version when `None`.
+  pub fn serves(&self, major: Option<u64>) -> bool {
+    major.map_or(true, |major| {
+      self.majors.is_empty() || self.majors.contains(&major)
+    })
+  }
+
+  /// The URL of the static release index this source points to, or `None` for a GitHub repository.
+  pub fn index_url(&self) -> Option<Url> {
+    Url::parse(&self.location)
+      .ok()
+      .filter(|url| url.scheme().len() > 1)
+  }
+}
+
+pub fn is_builtin(name: &str) -> bool {
+  name == PACT_5 || name == PACT_4
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct SourcesFile {
+  sources: Vec<Source>,
+}
+
+/// The built-in sources, queried when no sources are configured.
+///
+/// The Pact 4 repository also hosts the releases before Pact 4, so it isn't limited to a major version.
+pub fn builtin(config: &PactupConfig) -> Vec<Source> {
+  vec![
+    Source::new(PACT_5, &config.pact_5x_repo, vec![5]),
+    Source::new(PACT_4, &config.pact_4x_repo, vec![]),
+  ]
+}
+
+/// The configured sources, in the order they are queried.
+pub fn load(config: &PactupConfig) -> Result<Vec<Source>, Error> {
+  let path = config.sources_path();
+  let text = match std::fs::read_to_string(&path) {
+    Ok(text) => text,
+    Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(builtin(config)),
+    Err(source) => return Err(Error::CantRead { path, source }),
+  };
+  let file: SourcesFile =
+    serde_json::from_str(&text).map_err(|source| Error::CantDecode { path, source })?;
+
+  let builtin = builtin(config);
+  Ok(
+    file
+      .sources
+      .into_iter()
+      .map(|mut source| {
+        if let Some(builtin) = builtin.iter().find(|x| x.name == source.name) {
+          source.location.clone_from(&builtin.location);
+        }
+        source
+      })
+      .collect(),
+  )
+}
+
+/// Forgets the configured sources, going back to the built-in ones.
+pub fn reset(config: &PactupConfig) -> Result<(), Error> {
+  let path = config.sources_path();
+  match std::fs::remove_file(&path) {
+    Err(err) if err.kind() != std::io::ErrorKind::NotFound => {
+      Err(Error::CantWrite { path, source: err })
+    }
+    _ => Ok(()),
+  }
+}
+
+/// Stores `sources`, replacing the configured ones.
+pub fn save(config: &PactupConfig, sources: &[Source]) -> Result<(), Error> {
+  let path = config.sources_path();
+  let file = SourcesFile {
+    sources: sources.to_vec(),
+  };
+  let text = serde_json::to_string_pretty(&file).expect("Can't serialize the sources");
+  if let Some(parent) = path.parent() {
+    std::fs::create_dir_all(parent).map_err(|source| Error::CantWrite {
+      path: path.clone(),
+      source,
+    })?;
+  }
+  std::fs::write(&path, text).map_err(|source| Error::CantWrite { path, source })
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+  #[error("Can't read the sources from {}: {source}", path.display())]
+  CantRead {
+    path: PathBuf,
+    source: std::io::Error,
+  },
+  #[error("Can't decode the sources in {}: {source}", path.display())]
+  CantDecode {
+    path: PathBuf,
+    source: serde_json::Error,
+  },
+  #[error("Can't write the sources to {}: {source}", path.display())]
+  CantWrite {
+    path: PathBuf,
+    source: std::io::Error,
+  },
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use pretty_assertions::assert_eq;
+
+  #[test]
+  fn test_serves() {
+    let source = Source::new("internal", "acme/pact", vec![5]);
+    assert!(source.serves(Some(5)));
+    assert!(!source.serves(Some(4)));
+    assert!(source.serves(None));
+    assert!(Source::new("any", "acme/pact", vec![]).serves(Some(4)));
+  }
+
+  #[test]
+  fn test_index_url() {
+    assert_eq!(Source::new("fork", "acme/pact", vec![]).index_url(), None);
+    assert_eq!(
+      Source::new("mirror", "https://example.com/index.json", vec![])
+        .index_url()
+        .map(String::from),
+      Some("https://example.com/index.json".to_string())
+    );
+  }
+
+  #[test]
+  fn test_load_defaults_and_saved_sources() {
+    let base_dir = tempfile::tempdir().unwrap();
+    let config = PactupConfig::default().with_base_dir(Some(base_dir.path().to_path_buf()));
+    assert_eq!(load(&config).unwrap(), builtin(&config));
+
+    let sources = vec![
+      Source::new("internal", "acme/pact", vec![5]),
+      Source::new(PACT_4, "stale/location", vec![]),
+    ];
+    save(&config, &sources).unwrap();
+    assert_eq!(
+      load(&config).unwrap(),
+      vec![
+        Source::new("internal", "acme/pact", vec![5]),
+        Source::new(PACT_4, "kadena-io/pact", vec![]),
+      ]
+    );
+  }
+}
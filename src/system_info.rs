@@ -42,7 +42,36 @@ pub fn platform_arch() -> PlatformArch {
   not(any(target_arch = "arm", target_arch = "aarch64"))
 ))]
 pub fn platform_arch() -> PlatformArch {
-  PlatformArch::X64
+  if is_musl_host() {
+    PlatformArch::X64Musl
+  } else {
+    PlatformArch::X64
+  }
+}
+
+/// Whether the system uses musl as its libc, like Alpine does.
+///
+/// This is a property of the host, not of pactup itself, which may be a static musl
+/// build running on a glibc system. It's detected from the dynamic loader of `/bin/sh`,
+/// falling back to the output of `ldd --version`.
+#[cfg(target_os = "linux")]
+pub fn is_musl_host() -> bool {
+  if let Ok(Some(interpreter)) = crate::elf::interpreter(std::path::Path::new("/bin/sh")) {
+    return interpreter.contains("musl");
+  }
+  std::process::Command::new("ldd")
+    .arg("--version")
+    .output()
+    .is_ok_and(|output| {
+      // musl's ldd prints its version on stderr
+      String::from_utf8_lossy(&output.stderr).contains("musl")
+        || String::from_utf8_lossy(&output.stdout).contains("musl")
+    })
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn is_musl_host() -> bool {
+  false
 }
 
 #[derive(Clone, Copy, PartialEq, Eq, Debug, Hash)]
@@ -124,6 +153,7 @@ impl std::str::FromStr for PlatformArch {
     match s {
       "x86" => Ok(PlatformArch::X86),
       "x64" => Ok(PlatformArch::X64),
+      "x64-musl" => Ok(PlatformArch::X64Musl),
       "arm64" => Ok(PlatformArch::Arm64),
       "armv7l" => Ok(PlatformArch::Armv7l),
       "ppc64le" => Ok(PlatformArch::Ppc64le),
@@ -181,3 +211,135 @@ impl std::fmt::Display for Platform {
 pub fn get_platform() -> Platform {
   Platform::default()
 }
+
+/// A `major.minor` version, as used by Linux distros (`22.04`) and glibc (`2.35`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct DistroVersion {
+  pub major: u32,
+  pub minor: u32,
+}
+
+impl DistroVersion {
+  pub const fn new(major: u32, minor: u32) -> Self {
+    Self { major, minor }
+  }
+}
+
+impl std::str::FromStr for DistroVersion {
+  type Err = PlatformError;
+  fn from_str(s: &str) -> Result<DistroVersion, Self::Err> {
+    let invalid = || PlatformError::new(format!("Invalid version: {s}, expected e.g. 22.04"));
+    let (major, minor) = s.split_once('.').ok_or_else(invalid)?;
+    Ok(DistroVersion {
+      major: major.parse().map_err(|_| invalid())?,
+      minor: minor.parse().map_err(|_| invalid())?,
+    })
+  }
+}
+
+impl std::fmt::Display for DistroVersion {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    write!(f, "{}.{:02}", self.major, self.minor)
+  }
+}
+
+/// What is known about the Linux distribution pactup runs on.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LinuxDistro {
+  /// The `ID` field of `os-release`, e.g. `ubuntu`.
+  pub id: Option<String>,
+  /// The `VERSION_ID` field of `os-release`, e.g. `22.04`.
+  pub version: Option<DistroVersion>,
+  /// The version of the system glibc, if the system uses glibc.
+  pub glibc: Option<DistroVersion>,
+}
+
+impl LinuxDistro {
+  /// Detects the distro from `/etc/os-release`, and glibc from `ldd --version`.
+  pub fn detect() -> Self {
+    let os_release = ["/etc/os-release", "/usr/lib/os-release"]
+      .iter()
+      .find_map(|path| std::fs::read_to_string(path).ok())
+      .unwrap_or_default();
+    Self::from_os_release(&os_release, detect_glibc())
+  }
+
+  /// Reads the distro out of the contents of an `os-release` file.
+  pub fn from_os_release(contents: &str, glibc: Option<DistroVersion>) -> Self {
+    let field = |name: &str| {
+      contents.lines().find_map(|line| {
+        let (key, value) = line.trim().split_once('=')?;
+        (key == name).then(|| value.trim_matches(|c| c == '"' || c == '\'').to_string())
+      })
+    };
+    Self {
+      id: field("ID"),
+      version: field("VERSION_ID").and_then(|version| version.parse().ok()),
+      glibc,
+    }
+  }
+}
+
+/// The glibc version reported by `ldd --version`, or `None` on musl and other libcs.
+fn detect_glibc() -> Option<DistroVersion> {
+  let output = std::process::Command::new("ldd")
+    .arg("--version")
+    .output()
+    .ok()?;
+  parse_ldd_version(&String::from_utf8_lossy(&output.stdout))
+}
+
+/// Parses the first line of `ldd --version`, e.g. `ldd (Ubuntu GLIBC 2.35-0ubuntu3.8) 2.35`.
+fn parse_ldd_version(output: &str) -> Option<DistroVersion> {
+  let first_line = output.lines().next()?;
+  if !first_line.to_lowercase().contains("glibc") && !first_line.contains("GNU libc") {
+    return None;
+  }
+  first_line.split_whitespace().last()?.parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use pretty_assertions::assert_eq;
+
+  #[test]
+  fn test_linux_distro_from_os_release() {
+    let os_release = indoc::indoc! {r#"
+      NAME="Ubuntu"
+      VERSION_ID="22.04"
+      VERSION="22.04.4 LTS (Jammy Jellyfish)"
+      ID=ubuntu
+      ID_LIKE=debian
+    "#};
+    assert_eq!(
+      LinuxDistro::from_os_release(os_release, Some(DistroVersion::new(2, 35))),
+      LinuxDistro {
+        id: Some("ubuntu".to_string()),
+        version: Some(DistroVersion::new(22, 4)),
+        glibc: Some(DistroVersion::new(2, 35)),
+      }
+    );
+    assert_eq!(
+      LinuxDistro::from_os_release("ID=arch\n", None),
+      LinuxDistro {
+        id: Some("arch".to_string()),
+        version: None,
+        glibc: None,
+      }
+    );
+  }
+
+  #[test]
+  fn test_parse_ldd_version() {
+    assert_eq!(
+      parse_ldd_version("ldd (Ubuntu GLIBC 2.35-0ubuntu3.8) 2.35\nCopyright (C) 2022"),
+      Some(DistroVersion::new(2, 35))
+    );
+    assert_eq!(
+      parse_ldd_version("ldd (GNU libc) 2.39\n"),
+      Some(DistroVersion::new(2, 39))
+    );
+    assert_eq!(parse_ldd_version(""), None);
+  }
+}
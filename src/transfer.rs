@@ -0,0 +1,809 @@
+//! Downloads files into the cache, so interrupted transfers can be resumed.
+//!
+//! A file is first written to `<name>.part`, next to a `<name>.part.json` file recording the
+//! `ETag` or `Last-Modified` validator of the response. An interrupted transfer resumes with a
+//! `Range` request, sent along with the validator in `If-Range`, so the server sends the whole
+//! file again if it changed in the meantime.
+//!
+//! Large files may be downloaded in several segments at once, when the server accepts ranges.
+
+use crate::checksum::Sha256;
+use crate::download_cache;
+use crate::http::{self, header, StatusCode};
+use crate::progress::download_bar;
+use indicatif::{MultiProgress, ProgressBar};
+use log::debug;
+use serde::{Deserialize, Serialize};
+use std::ffi::OsString;
+use std::fs::{File, OpenOptions};
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use url::Url;
+
+/// Segments are never smaller than this, so that small files are downloaded in one go.
+const MIN_SEGMENT_SIZE: u64 = 1024 * 1024;
+
+/// How files are downloaded.
+#[derive(Debug, Clone)]
+pub struct Transfer {
+  /// The directory files are downloaded to.
+  pub downloads_dir: PathBuf,
+  /// How many segments of a large file to download at once.
+  pub connections: usize,
+  /// How many times an interrupted transfer is resumed before giving up.
+  pub retries: u32,
+  pub show_progress: bool,
+  /// The progress bars of the other transfers in progress, to draw the bar of this one along with them.
+  pub bars: Option<MultiProgress>,
+  /// Only use the archives already in the cache.
+  pub offline: bool,
+  /// The credentials of the GitHub API, to download the assets of private repositories.
+  pub credentials: Option<Credentials>,
+}
+
+/// A token sent to the GitHub API, and only to it, along with the requests downloading assets.
+#[derive(Debug, Clone)]
+pub struct Credentials {
+  pub api_url: Url,
+  pub token: String,
+}
+
+impl Credentials {
+  /// The headers downloading the asset at `url`, authenticated when `url` is in the API.
+  ///
+  /// GitHub redirects asset downloads to a storage host, to which the HTTP client doesn't forward
+  /// the `Authorization` header.
+  fn headers_for(&self, url: &Url) -> header::HeaderMap {
+    let mut headers = header::HeaderMap::new();
+    let in_api = url.origin() == self.api_url.origin()
+      && url
+        .path()
+        .starts_with(self.api_url.path().trim_end_matches('/'));
+    if !in_api {
+      return headers;
+    }
+    headers.insert(
+      header::ACCEPT,
+      header::HeaderValue::from_static("application/octet-stream"),
+    );
+    if let Ok(mut value) = header::HeaderValue::from_str(&format!("Bearer {}", self.token)) {
+      value.set_sensitive(true);
+      headers.insert(header::AUTHORIZATION, value);
+    }
+    headers
+  }
+}
+
+/// What is known about a partial download, stored next to it.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct State {
+  url: String,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  etag: Option<String>,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  last_modified: Option<String>,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  size: Option<u64>,
+  /// The byte ranges left to download, when downloading in segments.
+  #[serde(default, skip_serializing_if = "Vec::is_empty")]
+  segments: Vec<Segment>,
+}
+
+impl State {
+  fn new(url: &Url, response: &http::Response) -> Self {
+    let header = |name| {
+      response
+        .headers()
+        .get(name)
+        .and_then(|value| value.to_str().ok())
+        .map(String::from)
+    };
+    Self {
+      url: url.to_string(),
+      etag: header(header::ETAG).filter(|etag| !etag.starts_with("W/")),
+      last_modified: header(header::LAST_MODIFIED),
+      // Read from the header, as the body of a `HEAD` response is empty.
+      size: header(header::CONTENT_LENGTH).and_then(|size| size.parse().ok()),
+      segments: vec![],
+    }
+  }
+
+  /// The validator sent in `If-Range`. Without one, a transfer can't be resumed safely.
+  fn validator(&self) -> Option<&str> {
+    self.etag.as_deref().or(self.last_modified.as_deref())
+  }
+}
+
+/// A range of bytes, from `start` included to `end` excluded.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+struct Segment {
+  start: u64,
+  end: u64,
+}
+
+/// The paths of a download: the complete file, the partial file, and the state of the partial file.
+struct Paths {
+  complete: PathBuf,
+  part: PathBuf,
+  state: PathBuf,
+}
+
+impl Paths {
+  fn new(downloads_dir: &Path, url: &Url) -> Self {
+    let complete = download_cache::path_for(downloads_dir, url);
+    let with_suffix = |suffix| {
+      let mut path = OsString::from(complete.as_os_str());
+      path.push(suffix);
+      PathBuf::from(path)
+    };
+    Self {
+      part: with_suffix(".part"),
+      state: with_suffix(".part.json"),
+      complete,
+    }
+  }
+
+  fn read_state(&self, url: &Url) -> Option<State> {
+    let text = std::fs::read_to_string(&self.state).ok()?;
+    serde_json::from_str::<State>(&text)
+      .ok()
+      .filter(|state| state.url == url.as_str())
+  }
+
+  fn write_state(&self, state: &State) -> Result<(), Error> {
+    let text = serde_json::to_string(state).expect("Can't serialize the download state");
+    std::fs::write(&self.state, text).map_err(|source| Error::CantWrite {
+      path: self.state.clone(),
+      source,
+    })
+  }
+
+  /// Forgets the partial download, to start over.
+  fn discard(&self) {
+    std::fs::remove_file(&self.part).ok();
+    std::fs::remove_file(&self.state).ok();
+  }
+}
+
+impl Transfer {
+  /// The headers sent along with every request for the file at `url`.
+  fn headers_for(&self, url: &Url) -> header::HeaderMap {
+    self
+      .credentials
+      .as_ref()
+      .map(|credentials| credentials.headers_for(url))
+      .unwrap_or_default()
+  }
+
+  /// Returns the path of the file at `url` in the cache, or else downloads it, resuming a previous
+  /// partial download of it. A cached file must match the `expected` digest, if any.
+  pub fn fetch(&self, url: &Url, expected: Option<&Sha256>) -> Result<PathBuf, Error> {
+    if let Some(path) = download_cache::lookup(&self.downloads_dir, url, expected) {
+      return Ok(path);
+    }
+    if self.offline {
+      return Err(Error::Offline { url: url.clone() });
+    }
+    std::fs::create_dir_all(&self.downloads_dir).map_err(|source| Error::CantWrite {
+      path: self.downloads_dir.clone(),
+      source,
+    })?;
+    let paths = Paths::new(&self.downloads_dir, url);
+    let name = url
+      .path_segments()
+      .and_then(Iterator::last)
+      .unwrap_or_default();
+    let progress = download_bar(self.show_progress, self.bars.as_ref(), name);
+
+    let state = match paths.read_state(url) {
+      Some(state) => Some(state),
+      None => self.plan_segments(url, &paths)?,
+    };
+    let result = match state {
+      Some(state) if !state.segments.is_empty() => {
+        match self.fetch_segments(url, &paths, state, &progress) {
+          Err(Error::Changed) => {
+            debug!("{} changed on the server, downloading it again", url);
+            paths.discard();
+            self.fetch_sequentially(url, &paths, None, &progress)
+          }
+          result => result,
+        }
+      }
+      state => self.fetch_sequentially(url, &paths, state, &progress),
+    };
+    if self.show_progress {
+      progress.finish();
+      if self.bars.is_none() {
+        eprintln!();
+      }
+    }
+    result?;
+
+    std::fs::rename(&paths.part, &paths.complete).map_err(|source| Error::CantWrite {
+      path: paths.complete.clone(),
+      source,
+    })?;
+    std::fs::remove_file(&paths.state).ok();
+    download_cache::record(&paths.complete, url).map_err(|source| Error::CantWrite {
+      path: paths.complete.clone(),
+      source,
+    })?;
+    Ok(paths.complete)
+  }
+
+  /// Splits the file at `url` into segments, when it is large enough and the server accepts ranges.
+  fn plan_segments(&self, url: &Url, paths: &Paths) -> Result<Option<State>, Error> {
+    if self.connections < 2 {
+      return Ok(None);
+    }
+    let response = http::head_with_headers(url.as_str(), &self.headers_for(url))?;
+    let accepts_ranges = response
+      .headers()
+      .get(header::ACCEPT_RANGES)
+      .is_some_and(|value| value.as_bytes() == b"bytes");
+    let mut state = State::new(url, &response);
+    let (true, true, Some(size)) = (
+      response.status().is_success() && accepts_ranges,
+      state.validator().is_some(),
+      state.size,
+    ) else {
+      return Ok(None);
+    };
+    let count = self
+      .connections
+      .min(usize::try_from(size / MIN_SEGMENT_SIZE).unwrap_or(usize::MAX));
+    if count < 2 {
+      return Ok(None);
+    }
+
+    debug!("Downloading {} in {} segments", url, count);
+    let count = count as u64;
+    state.segments = (0..count)
+      .map(|i| Segment {
+        start: size * i / count,
+        end: size * (i + 1) / count,
+      })
+      .collect();
+    let file = File::create(&paths.part).map_err(|source| Error::CantWrite {
+      path: paths.part.clone(),
+      source,
+    })?;
+    file.set_len(size).map_err(|source| Error::CantWrite {
+      path: paths.part.clone(),
+      source,
+    })?;
+    paths.write_state(&state)?;
+    Ok(Some(state))
+  }
+
+  /// Downloads the file in one go, resuming from the end of the partial file when possible.
+  fn fetch_sequentially(
+    &self,
+    url: &Url,
+    paths: &Paths,
+    mut state: Option<State>,
+    progress: &ProgressBar,
+  ) -> Result<(), Error> {
+    let mut attempts = 0;
+    loop {
+      let validator = state.as_ref().and_then(State::validator);
+      let offset = match validator {
+        Some(_) => std::fs::metadata(&paths.part).map_or(0, |metadata| metadata.len()),
+        None => 0,
+      };
+      let mut headers = self.headers_for(url);
+      if let (Some(validator), true) = (validator, offset > 0) {
+        headers.insert(header::RANGE, range_header(offset, None));
+        if let Ok(validator) = header::HeaderValue::from_str(validator) {
+          headers.insert(header::IF_RANGE, validator);
+        }
+      }
+
+      let mut response = http::get_with_headers(url.as_str(), &headers)?;
+      let status = response.status();
+      let mut position = 0;
+      let open = if status == StatusCode::PARTIAL_CONTENT
+        && content_range_start(&response) == Some(offset)
+      {
+        debug!("Resuming the download of {} at {} bytes", url, offset);
+        position = offset;
+        OpenOptions::new().append(true).open(&paths.part)
+      } else if status == StatusCode::PARTIAL_CONTENT && offset > 0 {
+        // A range other than the one asked for can't be appended: start over.
+        debug!("{} sent another range than asked for, restarting", url);
+        paths.discard();
+        state = None;
+        continue;
+      } else if status == StatusCode::OK {
+        let new_state = State::new(url, &response);
+        paths.write_state(&new_state)?;
+        state = Some(new_state);
+        File::create(&paths.part)
+      } else if status == StatusCode::RANGE_NOT_SATISFIABLE
+        && state.as_ref().and_then(|state| state.size) == Some(offset)
+      {
+        // The partial file was complete already.
+        return Ok(());
+      } else if status == StatusCode::RANGE_NOT_SATISFIABLE {
+        paths.discard();
+        state = None;
+        continue;
+      } else {
+        return Err(Error::Status {
+          url: url.clone(),
+          status,
+        });
+      };
+      let mut file = open.map_err(|source| Error::CantWrite {
+        path: paths.part.clone(),
+        source,
+      })?;
+
+      let size = state.as_ref().and_then(|state| state.size);
+      progress.set_length(size.unwrap_or_default());
+      progress.set_position(position);
+      let result = receive(
+        &mut response,
+        &mut file,
+        &paths.part,
+        &mut position,
+        progress,
+      )
+      .and_then(|()| ensure_complete(position, size));
+      match result {
+        Err(Error::Interrupted { source }) if attempts < self.retries => {
+          debug!(
+            "The download of {} was interrupted ({}), resuming it",
+            url, source
+          );
+          attempts += 1;
+        }
+        result => return result,
+      }
+    }
+  }
+
+  /// Downloads the segments left at once, recording the ones left when interrupted.
+  fn fetch_segments(
+    &self,
+    url: &Url,
+    paths: &Paths,
+    mut state: State,
+    progress: &ProgressBar,
+  ) -> Result<(), Error> {
+    let size = state.size.unwrap_or_default();
+    let left: u64 = state.segments.iter().map(|x| x.end - x.start).sum();
+    progress.set_length(size);
+    progress.set_position(size - left);
+
+    let validator = state.validator().map(String::from);
+    let results: Vec<(Segment, Result<(), Error>)> = std::thread::scope(|scope| {
+      let handles: Vec<_> = state
+        .segments
+        .iter()
+        .map(|&segment| {
+          let validator = validator.as_deref();
+          scope.spawn(move || {
+            let mut segment = segment;
+            let result = self.fetch_segment(url, paths, validator, &mut segment, progress);
+            (segment, result)
+          })
+        })
+        .collect();
+      handles
+        .into_iter()
+        .map(|handle| handle.join().expect("A segment download panicked"))
+        .collect()
+    });
+
+    state.segments = results
+      .iter()
+      .map(|(segment, _)| *segment)
+      .filter(|segment| segment.start < segment.end)
+      .collect();
+    paths.write_state(&state)?;
+    let errors = results.into_iter().filter_map(|(_, result)| result.err());
+    // A changed file is worth starting over, so it wins over the other errors.
+    errors
+      .reduce(|error, other| match other {
+        Error::Changed => other,
+        _ => error,
+      })
+      .map_or(Ok(()), Err)
+  }
+
+  /// Downloads a segment into its place in the partial file, advancing its start as bytes arrive.
+  fn fetch_segment(
+    &self,
+    url: &Url,
+    paths: &Paths,
+    validator: Option<&str>,
+    segment: &mut Segment,
+    progress: &ProgressBar,
+  ) -> Result<(), Error> {
+    let mut file = OpenOptions::new()
+      .write(true)
+      .open(&paths.part)
+      .map_err(|source| Error::CantWrite {
+        path: paths.part.clone(),
+        source,
+      })?;
+    let mut attempts = 0;
+    while segment.start < segment.end {
+      let mut headers = self.headers_for(url);
+      headers.insert(
+        header::RANGE,
+        range_header(segment.start, Some(segment.end)),
+      );
+      if let Some(validator) = validator.and_then(|x| header::HeaderValue::from_str(x).ok()) {
+        headers.insert(header::IF_RANGE, validator);
+      }
+      let mut response = http::get_with_headers(url.as_str(), &headers)?;
+      if response.status() != StatusCode::PARTIAL_CONTENT
+        || content_range_start(&response) != Some(segment.start)
+      {
+        return Err(Error::Changed);
+      }
+
+      file
+        .seek(SeekFrom::Start(segment.start))
+        .map_err(|source| Error::CantWrite {
+          path: paths.part.clone(),
+          source,
+        })?;
+      let result = receive(
+        &mut response,
+        &mut file,
+        &paths.part,
+        &mut segment.start,
+        progress,
+      )
+      .and_then(|()| ensure_complete(segment.start, Some(segment.end)));
+      match result {
+        Err(Error::Interrupted { source }) if attempts < self.retries => {
+          debug!(
+            "A segment of {} was interrupted ({}), resuming it",
+            url, source
+          );
+          attempts += 1;
+        }
+        result => return result,
+      }
+    }
+    Ok(())
+  }
+}
+
+/// A `Range` header for the bytes from `start` to `end` excluded, or to the end of the file.
+fn range_header(start: u64, end: Option<u64>) -> header::HeaderValue {
+  let range = match end {
+    Some(end) => format!("bytes={start}-{}", end - 1),
+    None => format!("bytes={start}-"),
+  };
+  header::HeaderValue::from_str(&range).expect("A range is a valid header value")
+}
+
+/// The first byte sent in a partial response, from its `Content-Range` header, e.g. `bytes 100-199/1000`.
+fn content_range_start(response: &http::Response) -> Option<u64> {
+  let value = response
+    .headers()
+    .get(header::CONTENT_RANGE)?
+    .to_str()
+    .ok()?;
+  let (start, _) = value.strip_prefix("bytes ")?.split_once('-')?;
+  start.parse().ok()
+}
+
+/// Copies `reader` into `file`, advancing `position` and `progress` as bytes are written.
+/// Reading errors mean the connection dropped, and are reported as [`Error::Interrupted`].
+fn receive(
+  reader: &mut impl Read,
+  file: &mut File,
+  path: &Path,
+  position: &mut u64,
+  progress: &ProgressBar,
+) -> Result<(), Error> {
+  let mut buffer = vec![0; 64 * 1024];
+  loop {
+    let count = match reader.read(&mut buffer) {
+      Ok(0) => return Ok(()),
+      Ok(count) => count,
+      Err(err) if err.kind() == std::io::ErrorKind::Interrupted => continue,
+      Err(source) => return Err(Error::Interrupted { source }),
+    };
+    file
+      .write_all(&buffer[..count])
+      .map_err(|source| Error::CantWrite {
+        path: path.to_path_buf(),
+        source,
+      })?;
+    *position += count as u64;
+    progress.inc(count as u64);
+  }
+}
+
+/// Checks that a response that ended at `position` wasn't cut short of `end`.
+fn ensure_complete(position: u64, end: Option<u64>) -> Result<(), Error> {
+  match end {
+    Some(end) if position < end => Err(Error::Interrupted {
+      source: std::io::ErrorKind::UnexpectedEof.into(),
+    }),
+    _ => Ok(()),
+  }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+  #[error(transparent)]
+  Http {
+    #[from]
+    source: http::Error,
+  },
+  #[error("{url} answered {status}")]
+  Status { url: Url, status: StatusCode },
+  #[error("The download was interrupted: {source}. Run the command again to resume it.")]
+  Interrupted { source: std::io::Error },
+  #[error("{url} isn't in the download cache, and can't be downloaded while offline")]
+  Offline { url: Url },
+  #[error("The file changed on the server during the download")]
+  Changed,
+  #[error("Can't write the download to {}: {source}", path.display())]
+  CantWrite {
+    path: PathBuf,
+    source: std::io::Error,
+  },
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use pretty_assertions::assert_eq;
+  use std::io::{BufRead, BufReader};
+  use std::net::TcpStream;
+  use std::sync::atomic::{AtomicUsize, Ordering};
+  use std::sync::{Arc, Mutex};
+
+  /// A file served over HTTP with an `ETag`, honoring `Range` and `If-Range`.
+  #[derive(Default)]
+  struct Served {
+    content: Mutex<(Vec<u8>, String)>,
+    /// How many of the next responses to cut short, as if the connection dropped.
+    cut: AtomicUsize,
+    /// The `Range` header of each `GET` request.
+    ranges: Mutex<Vec<Option<String>>>,
+    /// How many of the next range requests to answer with another range than asked for.
+    misaligned: AtomicUsize,
+    /// The `Authorization` header of each request.
+    authorizations: Mutex<Vec<Option<String>>>,
+  }
+
+  impl Served {
+    fn set(&self, content: Vec<u8>, etag: &str) {
+      *self.content.lock().unwrap() = (content, format!("\"{etag}\""));
+    }
+
+    fn ranges(&self) -> Vec<Option<String>> {
+      self.ranges.lock().unwrap().clone()
+    }
+
+    fn respond(&self, mut stream: TcpStream) {
+      let mut reader = BufReader::new(stream.try_clone().unwrap());
+      let mut request = String::new();
+      reader.read_line(&mut request).unwrap();
+      let mut headers = vec![];
+      let mut line = String::new();
+      while reader.read_line(&mut line).unwrap() > 2 {
+        let (name, value) = line.split_once(':').unwrap();
+        headers.push((name.to_ascii_lowercase(), value.trim().to_string()));
+        line.clear();
+      }
+      let header = |name: &str| {
+        headers
+          .iter()
+          .find(|(x, _)| x == name)
+          .map(|(_, value)| value.clone())
+      };
+
+      self
+        .authorizations
+        .lock()
+        .unwrap()
+        .push(header("authorization"));
+      let (content, etag) = self.content.lock().unwrap().clone();
+      let size = content.len();
+      if request.starts_with("HEAD") {
+        write!(stream, "HTTP/1.1 200 OK\r\nContent-Length: {size}\r\nAccept-Ranges: bytes\r\nETag: {etag}\r\nConnection: close\r\n\r\n").unwrap();
+        return;
+      }
+
+      let range = header("range");
+      self.ranges.lock().unwrap().push(range.clone());
+      let range = range
+        .filter(|_| header("if-range").is_none_or(|x| x == etag))
+        .and_then(|range| {
+          let (start, end) = range.strip_prefix("bytes=")?.split_once('-')?;
+          let end = end.parse::<usize>().map_or(size, |end| end + 1);
+          Some((start.parse::<usize>().ok()?, end))
+        });
+      let misaligned = range.is_some()
+        && self
+          .misaligned
+          .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |x| x.checked_sub(1))
+          .is_ok();
+      let (status, start, end) = match range {
+        Some((start, end)) if misaligned => ("206 Partial Content", start / 2, end),
+        Some((start, end)) => ("206 Partial Content", start, end),
+        None => ("200 OK", 0, size),
+      };
+      write!(
+        stream,
+        "HTTP/1.1 {status}\r\nContent-Length: {}\r\nContent-Range: bytes {start}-{}/{size}\r\nETag: {etag}\r\nConnection: close\r\n\r\n",
+        end - start,
+        end - 1,
+      )
+      .unwrap();
+      let cut = self
+        .cut
+        .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |x| x.checked_sub(1))
+        .is_ok();
+      let end = if cut { start + (end - start) / 2 } else { end };
+      stream.write_all(&content[start..end]).ok();
+    }
+  }
+
+  /// Serves `content` on a local port, and returns its URL.
+  fn serve(content: Vec<u8>, cut: usize) -> (Url, Arc<Served>) {
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}/pact.tar.gz", listener.local_addr().unwrap());
+    let served = Arc::new(Served::default());
+    served.set(content, "v1");
+    served.cut.store(cut, Ordering::SeqCst);
+    let server = Arc::clone(&served);
+    std::thread::spawn(move || {
+      for stream in listener.incoming() {
+        let server = Arc::clone(&server);
+        std::thread::spawn(move || server.respond(stream.unwrap()));
+      }
+    });
+    (url.parse().unwrap(), served)
+  }
+
+  fn content(size: usize) -> Vec<u8> {
+    (0..size).map(|i| u8::try_from(i % 251).unwrap()).collect()
+  }
+
+  fn transfer(downloads_dir: &Path, connections: usize, retries: u32) -> Transfer {
+    Transfer {
+      downloads_dir: downloads_dir.to_path_buf(),
+      connections,
+      retries,
+      show_progress: false,
+      bars: None,
+      offline: false,
+      credentials: None,
+    }
+  }
+
+  #[test]
+  fn test_resumes_interrupted_downloads() {
+    let downloads_dir = tempfile::tempdir().unwrap();
+    let (url, served) = serve(content(100_000), 1);
+
+    let path = transfer(downloads_dir.path(), 1, 1)
+      .fetch(&url, None)
+      .unwrap();
+    assert_eq!(std::fs::read(&path).unwrap(), content(100_000));
+    let ranges = served.ranges();
+    assert_eq!(ranges.len(), 2);
+    assert_eq!(ranges[0], None);
+    assert!(ranges[1].as_ref().is_some_and(|x| x != "bytes=0-"));
+    let partial = std::fs::read_dir(downloads_dir.path())
+      .unwrap()
+      .filter(|file| {
+        file
+          .as_ref()
+          .unwrap()
+          .path()
+          .to_string_lossy()
+          .contains(".part")
+      });
+    assert_eq!(partial.count(), 0);
+
+    // The complete file is served from the cache from now on.
+    let cached = transfer(downloads_dir.path(), 1, 1)
+      .fetch(&url, None)
+      .unwrap();
+    assert_eq!(cached, path);
+    assert_eq!(served.ranges().len(), 2);
+  }
+
+  #[test]
+  fn test_resumes_downloads_across_runs() {
+    let downloads_dir = tempfile::tempdir().unwrap();
+    let (url, served) = serve(content(100_000), 1);
+
+    let result = transfer(downloads_dir.path(), 1, 0).fetch(&url, None);
+    assert!(matches!(result, Err(Error::Interrupted { .. })));
+    let path = transfer(downloads_dir.path(), 1, 0)
+      .fetch(&url, None)
+      .unwrap();
+    assert_eq!(std::fs::read(path).unwrap(), content(100_000));
+    assert!(served.ranges()[1].is_some());
+  }
+
+  #[test]
+  fn test_restarts_downloads_of_changed_files() {
+    let downloads_dir = tempfile::tempdir().unwrap();
+    let (url, served) = serve(content(100_000), 1);
+
+    let result = transfer(downloads_dir.path(), 1, 0).fetch(&url, None);
+    assert!(matches!(result, Err(Error::Interrupted { .. })));
+    let changed: Vec<u8> = content(80_000).into_iter().rev().collect();
+    served.set(changed.clone(), "v2");
+    let path = transfer(downloads_dir.path(), 1, 0)
+      .fetch(&url, None)
+      .unwrap();
+    assert_eq!(std::fs::read(path).unwrap(), changed);
+  }
+
+  #[test]
+  fn test_restarts_downloads_resumed_at_another_offset() {
+    let downloads_dir = tempfile::tempdir().unwrap();
+    let (url, served) = serve(content(100_000), 1);
+    served.misaligned.store(1, Ordering::SeqCst);
+
+    let path = transfer(downloads_dir.path(), 1, 1)
+      .fetch(&url, None)
+      .unwrap();
+    assert_eq!(std::fs::read(path).unwrap(), content(100_000));
+    let ranges = served.ranges();
+    assert_eq!(ranges.len(), 3);
+    assert!(ranges[1].is_some());
+    assert_eq!(ranges[2], None);
+  }
+
+  #[test]
+  fn test_sends_credentials_to_the_api_only() {
+    let downloads_dir = tempfile::tempdir().unwrap();
+    let (url, served) = serve(content(1000), 0);
+    let credentials = |api_url: Url| Credentials {
+      api_url,
+      token: "secret".to_string(),
+    };
+
+    Transfer {
+      credentials: Some(credentials("https://api.github.com".parse().unwrap())),
+      ..transfer(downloads_dir.path(), 1, 0)
+    }
+    .fetch(&url, None)
+    .unwrap();
+    download_cache::clean(downloads_dir.path()).unwrap();
+    Transfer {
+      credentials: Some(credentials(url.join("/").unwrap())),
+      ..transfer(downloads_dir.path(), 1, 0)
+    }
+    .fetch(&url, None)
+    .unwrap();
+    assert_eq!(
+      *served.authorizations.lock().unwrap(),
+      vec![None, Some("Bearer secret".to_string())]
+    );
+  }
+
+  #[test]
+  fn test_downloads_in_segments() {
+    let downloads_dir = tempfile::tempdir().unwrap();
+    let size = 3 * usize::try_from(MIN_SEGMENT_SIZE).unwrap() + 1000;
+    let (url, served) = serve(content(size), 1);
+
+    let path = transfer(downloads_dir.path(), 3, 1)
+      .fetch(&url, None)
+      .unwrap();
+    assert_eq!(std::fs::read(path).unwrap(), content(size));
+    let ranges = served.ranges();
+    assert_eq!(ranges.len(), 4);
+    assert!(ranges.iter().all(Option::is_some));
+  }
+}
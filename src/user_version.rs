@@ -87,6 +87,15 @@ impl UserVersion {
     }
   }
 
+  /// The major version this user version is limited to, if any.
+  pub fn major(&self) -> Option<u64> {
+    match self {
+      Self::OnlyMajor(major) | Self::MajorMinor(major, _) => Some(*major),
+      Self::Full(Version::Semver(version)) => Some(version.major),
+      Self::SemverRange(_) | Self::Full(_) => None,
+    }
+  }
+
   /// Get the inferred alias for special versions (latest, nightly)
   pub fn inferred_alias(&self) -> Option<Version> {
     match self {
@@ -290,4 +299,22 @@ mod tests {
       assert_eq!(version.inferred_alias(), expected, "{}", message);
     }
   }
+
+  #[test]
+  fn test_major() {
+    let cases = [
+      ("4", Some(4)),
+      ("4.13", Some(4)),
+      ("v5.0.0", Some(5)),
+      ("nightly", None),
+      ("^4.0.0 || ^5.0.0", None),
+    ];
+    for (input, expected) in cases {
+      assert_eq!(
+        input.parse::<UserVersion>().unwrap().major(),
+        expected,
+        "{input}"
+      );
+    }
+  }
 }
@@ -1,4 +1,3 @@
-use serde::Serialize;
 use std::path::PathBuf;
 use std::str::FromStr;
 
@@ -6,7 +5,7 @@ use crate::alias;
 use crate::config;
 use crate::system_version;
 
-#[derive(Debug, PartialEq, PartialOrd, Eq, Ord, Clone, Serialize)]
+#[derive(Debug, PartialEq, PartialOrd, Eq, Ord, Clone)]
 pub enum Version {
   Semver(node_semver::Version),
   Alias(String),
@@ -117,11 +116,25 @@ impl Version {
     path.is_dir() && path.exists() && path != &config.installations_dir()
   }
 
+  /// Whether this version can name an installation in the installations directory.
+  pub fn is_installable(&self) -> bool {
+    matches!(self, Self::Semver(_) | Self::Nightly(_))
+  }
+
   pub fn is_nightly(&self) -> bool {
     matches!(self, Self::Nightly(_))
   }
 }
 
+impl serde::Serialize for Version {
+  fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+  where
+    S: serde::Serializer,
+  {
+    serializer.collect_str(self)
+  }
+}
+
 impl<'de> serde::Deserialize<'de> for Version {
   fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
   where